@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use opencode_share_client::models::ShareData;
+use opencode_share_client::{RetryPolicy, ShareClient};
+use std::path::PathBuf;
+
+/// Publish a transcript to an opencode share server.
+///
+/// The file holds one share item per line, as sent to `/api/share/:id/sync`:
+/// `{"_key": "session", "type": "session", "data": {"id": "ses_1", "title": "..."}}`.
+#[derive(Debug, Parser)]
+#[command(name = "opencode-share-upload", version)]
+struct Args {
+    /// JSONL transcript to upload
+    file: PathBuf,
+    /// Share server base URL
+    #[arg(long, env = "OPENCODE_SHARE_URL")]
+    server: String,
+    /// Session ID for the share; defaults to the `id` of the transcript's session item
+    #[arg(long)]
+    session_id: Option<String>,
+    /// Items per sync request
+    #[arg(long, default_value_t = 1000)]
+    batch_size: usize,
+    /// Retries for failed requests
+    #[arg(long, default_value_t = 3)]
+    retries: u32,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let transcript = tokio::fs::read_to_string(&args.file)
+        .await
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let items = transcript
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str::<ShareData>(line)
+                .with_context(|| format!("Invalid JSON on line {}", index + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let session_id = match args.session_id {
+        Some(id) => id,
+        None => items
+            .iter()
+            .find(|item| item.get("type").and_then(|t| t.as_str()) == Some("session"))
+            .and_then(|item| item.pointer("/data/id")?.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("No session item with an id; pass --session-id"))?,
+    };
+
+    let client = ShareClient::new(args.server)
+        .with_retry(RetryPolicy {
+            max_retries: args.retries,
+            ..RetryPolicy::default()
+        })
+        .with_batch_items(args.batch_size);
+
+    let share = client.create_share(&session_id).await?;
+    if let Err(e) = client.sync_share(&share.id, &share.secret, &items).await {
+        // Leave nothing half-uploaded behind
+        let _ = client.remove_share(&share.id, &share.secret).await;
+        return Err(e.into());
+    }
+
+    eprintln!("Uploaded {} items", items.len());
+    println!("{}", share.url);
+    eprintln!("Secret: {}", share.secret);
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use reqwest::{Method, Response, StatusCode};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::models::{
+    CreateShareRequest, CreateShareResponse, RemoveShareRequest, ShareData, SyncShareRequest,
+};
+
+/// Items per sync request by default; the server's default `limits.max_sync_items`.
+const DEFAULT_BATCH_ITEMS: usize = 1000;
+
+/// Serialized bytes per sync request by default, half the server's default body limit.
+const DEFAULT_BATCH_BYTES: usize = 8 * 1024 * 1024;
+
+/// Errors returned by [`ShareClient`].
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The request could not be sent or the response could not be read.
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The server answered with a non-success status.
+    #[error("Server returned {status}: {body}")]
+    Status { status: u16, body: String },
+    /// An item could not be serialized.
+    #[error("Invalid share item: {0}")]
+    Item(#[from] serde_json::Error),
+}
+
+impl ClientError {
+    /// HTTP status returned by the server, if the request got that far.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Whether the request may be sent again. A non-idempotent request is only retried
+    /// when it never reached the server.
+    fn is_retryable(&self, idempotent: bool) -> bool {
+        if !idempotent {
+            return matches!(self, ClientError::Http(e) if e.is_connect());
+        }
+        match self {
+            ClientError::Http(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+            ClientError::Status { status, .. } => {
+                let status = StatusCode::from_u16(*status).unwrap_or(StatusCode::OK);
+                status == StatusCode::TOO_MANY_REQUESTS
+                    || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+            }
+            ClientError::Item(_) => false,
+        }
+    }
+}
+
+/// How failed requests are retried: connection errors, timeouts, `429` and `5xx` responses
+/// are retried with exponential backoff, or after the delay a `429`'s `Retry-After` asks for;
+/// other errors are returned immediately. Creating a share is not idempotent and is only
+/// retried on connection errors.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// Client for a share server's `/api` endpoints.
+#[derive(Debug, Clone)]
+pub struct ShareClient {
+    http: reqwest::Client,
+    base_url: String,
+    retry: RetryPolicy,
+    batch_items: usize,
+    batch_bytes: usize,
+}
+
+impl ShareClient {
+    /// Client for the server at `base_url`, e.g. `https://share.example.com`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            retry: RetryPolicy::default(),
+            batch_items: DEFAULT_BATCH_ITEMS,
+            batch_bytes: DEFAULT_BATCH_BYTES,
+        }
+    }
+
+    /// Use a preconfigured HTTP client, e.g. with timeouts or a proxy.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Override the default retry policy.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Limit the items sent per sync request; match the server's `limits.max_sync_items`
+    /// if it was lowered.
+    pub fn with_batch_items(mut self, items: usize) -> Self {
+        self.batch_items = items.max(1);
+        self
+    }
+
+    /// Limit the serialized item bytes sent per sync request; keep it below the server's
+    /// `limits.max_body_bytes`.
+    pub fn with_batch_bytes(mut self, bytes: usize) -> Self {
+        self.batch_bytes = bytes;
+        self
+    }
+
+    /// Create a share for `session_id`; keep the returned secret to sync or remove it.
+    pub async fn create_share(&self, session_id: &str) -> Result<CreateShareResponse, ClientError> {
+        let request = CreateShareRequest {
+            session_id: session_id.to_string(),
+        };
+        let response = self
+            .send(Method::POST, "/api/share", Some(&request), false)
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Add or replace items of a share, split into as many requests as the batch limits
+    /// require. Items with the same `_key` replace each other, so retrying a batch is safe
+    /// as long as every item has a `_key`.
+    pub async fn sync_share(
+        &self,
+        share_id: &str,
+        secret: &str,
+        data: &[ShareData],
+    ) -> Result<(), ClientError> {
+        let path = format!("/api/share/{}/sync", share_id);
+        for batch in self.batches(data)? {
+            let request = SyncShareRequest {
+                secret: secret.to_string(),
+                data: batch.to_vec(),
+            };
+            self.send(Method::POST, &path, Some(&request), true).await?;
+        }
+        Ok(())
+    }
+
+    /// The items stored for a share.
+    pub async fn get_share_data(&self, share_id: &str) -> Result<Vec<ShareData>, ClientError> {
+        let path = format!("/api/share/{}/data", share_id);
+        let response = self.send::<()>(Method::GET, &path, None, true).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Delete a share.
+    pub async fn remove_share(&self, share_id: &str, secret: &str) -> Result<(), ClientError> {
+        let request = RemoveShareRequest {
+            secret: secret.to_string(),
+        };
+        let path = format!("/api/share/{}", share_id);
+        self.send(Method::DELETE, &path, Some(&request), true).await?;
+        Ok(())
+    }
+
+    /// Split `data` into consecutive slices within the batch limits. An item larger than
+    /// `batch_bytes` is sent on its own and left for the server to accept or reject.
+    fn batches<'a>(&self, data: &'a [ShareData]) -> Result<Vec<&'a [ShareData]>, ClientError> {
+        let mut batches = Vec::new();
+        let mut start = 0;
+        let mut bytes = 0;
+
+        for (index, item) in data.iter().enumerate() {
+            let size = serde_json::to_vec(item)?.len();
+            let full = index - start == self.batch_items || bytes + size > self.batch_bytes;
+            if index > start && full {
+                batches.push(&data[start..index]);
+                start = index;
+                bytes = 0;
+            }
+            bytes += size;
+        }
+        if start < data.len() {
+            batches.push(&data[start..]);
+        }
+
+        Ok(batches)
+    }
+
+    async fn send<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+        idempotent: bool,
+    ) -> Result<Response, ClientError> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut retry = 0;
+
+        loop {
+            let mut request = self.http.request(method.clone(), &url);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let (result, retry_after) = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let retry_after = retry_after(&response);
+                    let error = ClientError::Status {
+                        status: response.status().as_u16(),
+                        body: response.text().await.unwrap_or_default(),
+                    };
+                    (Err(error), retry_after)
+                }
+                Err(e) => (Err(ClientError::Http(e)), None),
+            };
+
+            match result {
+                Err(e) if retry < self.retry.max_retries && e.is_retryable(idempotent) => {
+                    let delay = retry_after.unwrap_or_else(|| self.retry.backoff(retry));
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+/// Delay requested by a `429` response's `Retry-After` header, when given in seconds.
+fn retry_after(response: &Response) -> Option<Duration> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let seconds = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
@@ -0,0 +1,21 @@
+//! Client for the opencode share server API.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), opencode_share_client::ClientError> {
+//! use opencode_share_client::ShareClient;
+//! use serde_json::json;
+//!
+//! let client = ShareClient::new("https://share.example.com");
+//! let share = client.create_share("ses_123").await?;
+//! client
+//!     .sync_share(&share.id, &share.secret, &[json!({ "_key": "session", "type": "session", "data": { "title": "CI run" } })])
+//!     .await?;
+//! println!("{}", share.url);
+//! # Ok(())
+//! # }
+//! ```
+
+mod client;
+pub mod models;
+
+pub use client::{ClientError, RetryPolicy, ShareClient};
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// ShareData is now an arbitrary JSON value.
+/// Clients should include a `_key` field to identify how data should be merged.
+/// Optional `_type` field can be used by clients for their own type identification.
+///
+/// Example:
+/// ```json
+/// {
+///   "_key": "session",
+///   "_type": "session",
+///   "id": "xxx",
+///   "title": "...",
+///   ... // any other fields
+/// }
+/// ```
+pub type ShareData = Value;
+
+// Create share request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateShareRequest {
+    #[serde(rename = "sessionID")]
+    pub session_id: String,
+}
+
+// Create share response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateShareResponse {
+    pub id: String,
+    pub secret: String,
+    pub url: String,
+}
+
+// Sync share request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncShareRequest {
+    pub secret: String,
+    pub data: Vec<ShareData>,
+}
+
+// Remove share request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveShareRequest {
+    pub secret: String,
+}
@@ -0,0 +1,5 @@
+-- Track the serialized size of each share's data for storage quotas
+ALTER TABLE shares ADD COLUMN IF NOT EXISTS storage_bytes BIGINT NOT NULL DEFAULT 0;
+
+-- Backfill existing shares
+UPDATE shares SET storage_bytes = octet_length(data::text) WHERE data IS NOT NULL;
@@ -0,0 +1,2 @@
+-- Per-share report of secrets masked during sync, keyed by item key
+ALTER TABLE shares ADD COLUMN IF NOT EXISTS redactions JSONB NOT NULL DEFAULT '{}';
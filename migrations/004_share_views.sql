@@ -0,0 +1,14 @@
+-- Share page views and data fetches, for per-share analytics
+CREATE TABLE IF NOT EXISTS share_views (
+    id BIGSERIAL PRIMARY KEY,
+    share_id TEXT NOT NULL REFERENCES shares(id) ON DELETE CASCADE,
+    -- 'page' for the share page, 'data' for API data fetches
+    kind TEXT NOT NULL,
+    viewed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Salted per-share hash, never the raw IP
+    ip_hash TEXT NOT NULL,
+    user_agent_class TEXT NOT NULL,
+    referrer TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_views_share_id_viewed_at ON share_views(share_id, viewed_at);
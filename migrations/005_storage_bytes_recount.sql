@@ -0,0 +1,5 @@
+-- The 002 backfill measured jsonb's spaced text output, while syncs store the size of
+-- compact JSON. Flag those rows so the server recounts them on startup.
+ALTER TABLE shares ADD COLUMN IF NOT EXISTS storage_bytes_stale BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE shares SET storage_bytes_stale = TRUE WHERE data IS NOT NULL;
@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::core::export::render_html_export;
+use crate::core::markdown::render_markdown;
+use crate::core::transcript::Transcript;
+use crate::models::ShareListQuery;
+use crate::AppState;
+
+/// Command line of the `opencode-share` binary. Without a subcommand it serves HTTP.
+#[derive(Debug, Parser)]
+#[command(name = "opencode-share", version, about = "Share server for opencode sessions")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run pending migrations and serve HTTP (the default)
+    Serve,
+    /// Run pending migrations and exit
+    Migrate,
+    /// Inspect and manage stored shares
+    #[command(subcommand)]
+    Share(ShareCommand),
+    /// Delete abandoned shares and, optionally, old view records
+    Gc(GcArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ShareCommand {
+    /// List shares, most recently updated first
+    List {
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+        #[arg(long, default_value_t = 0)]
+        offset: u32,
+        /// Print a JSON array instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a share's details
+    Show { id: String },
+    /// Delete a share without its secret
+    Delete { id: String },
+    /// Export a share's conversation
+    Export {
+        id: String,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Md)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Formats of `share export`, matching the `/api/share/:id/...` export endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Markdown, as served by `export.md`
+    Md,
+    /// The stored items, as served by `data`
+    Json,
+    /// Standalone page, as served by `export.html`
+    Html,
+}
+
+#[derive(Debug, Args)]
+pub struct GcArgs {
+    /// Delete shares that never received any data after this many hours
+    #[arg(long, default_value_t = 24)]
+    pub abandoned_after_hours: u32,
+    /// Delete view records older than this many days; views are kept when unset
+    #[arg(long)]
+    pub views_older_than_days: Option<u32>,
+}
+
+/// Run a `share` subcommand against the configured database, printing to `out`.
+pub async fn run_share<W: Write>(state: &AppState, command: ShareCommand, out: &mut W) -> Result<()> {
+    let service = state.share_service();
+
+    match command {
+        ShareCommand::List { limit, offset, json } => {
+            let shares = service
+                .list(&ShareListQuery {
+                    limit: limit.into(),
+                    offset: offset.into(),
+                    ..ShareListQuery::default()
+                })
+                .await?;
+            if json {
+                serde_json::to_writer_pretty(&mut *out, &shares)?;
+                writeln!(out)?;
+                return Ok(());
+            }
+
+            writeln!(out, "{:<32} {:<20} {:>10}  TITLE", "ID", "UPDATED", "BYTES")?;
+            for share in shares {
+                writeln!(
+                    out,
+                    "{:<32} {:<20} {:>10}  {}",
+                    share.id,
+                    share.updated_at.format("%Y-%m-%d %H:%M:%S"),
+                    share.storage_bytes,
+                    share.title.unwrap_or_default()
+                )?;
+            }
+        }
+        ShareCommand::Show { id } => {
+            let share = service
+                .get(&id)
+                .await?
+                .ok_or_else(|| anyhow!("Share not found: {}", id))?;
+            let items = match &share.data {
+                Some(Value::Array(items)) => items.as_slice(),
+                _ => &[],
+            };
+            let transcript = Transcript::from_items(items);
+            let redactions: u64 = share
+                .redactions
+                .as_object()
+                .into_iter()
+                .flat_map(|items| items.values())
+                .filter_map(Value::as_object)
+                .flat_map(|rules| rules.values())
+                .filter_map(Value::as_u64)
+                .sum();
+
+            writeln!(out, "ID:          {}", share.id)?;
+            writeln!(out, "Session:     {}", share.session_id)?;
+            writeln!(out, "Title:       {}", transcript.title().unwrap_or(""))?;
+            if let Some(base_url) = state.public_url.configured() {
+                writeln!(out, "URL:         {}/share/{}", base_url, share.id)?;
+            }
+            writeln!(out, "Created:     {}", share.created_at.to_rfc3339())?;
+            writeln!(out, "Updated:     {}", share.updated_at.to_rfc3339())?;
+            writeln!(out, "Bytes:       {}", share.storage_bytes)?;
+            writeln!(out, "Items:       {}", items.len())?;
+            writeln!(out, "Messages:    {}", transcript.messages().len())?;
+            writeln!(out, "Files:       {}", transcript.files_changed())?;
+            writeln!(out, "Redactions:  {}", redactions)?;
+        }
+        ShareCommand::Delete { id } => {
+            if !service.delete(&id).await? {
+                return Err(anyhow!("Share not found: {}", id));
+            }
+            writeln!(out, "Deleted share {}", id)?;
+        }
+        ShareCommand::Export { id, format, output } => {
+            let items = match service.get(&id).await? {
+                Some(share) => match share.data {
+                    Some(Value::Array(items)) => items,
+                    _ => Vec::new(),
+                },
+                None => return Err(anyhow!("Share not found: {}", id)),
+            };
+
+            let exported = match format {
+                ExportFormat::Md => render_markdown(&Transcript::from_items(&items)),
+                ExportFormat::Json => serde_json::to_string_pretty(&items)? + "\n",
+                ExportFormat::Html => {
+                    render_html_export(
+                        &state.templates,
+                        &state.settings.paths.static_dir,
+                        &id,
+                        &items,
+                        state.public_url.configured(),
+                    )
+                    .await?
+                }
+            };
+
+            match output {
+                Some(path) => tokio::fs::write(&path, exported).await?,
+                None => out.write_all(exported.as_bytes())?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `gc`, printing what was deleted to `out`.
+pub async fn run_gc<W: Write>(state: &AppState, args: GcArgs, out: &mut W) -> Result<()> {
+    let now = Utc::now();
+
+    let before = now
+        .checked_sub_signed(Duration::hours(args.abandoned_after_hours.into()))
+        .ok_or_else(|| anyhow!("--abandoned-after-hours is out of range"))?;
+    let shares = state.share_service().delete_abandoned(before).await?;
+    writeln!(out, "Deleted {} abandoned shares", shares)?;
+
+    if let Some(days) = args.views_older_than_days {
+        let before = now
+            .checked_sub_signed(Duration::days(days.into()))
+            .ok_or_else(|| anyhow!("--views-older-than-days is out of range"))?;
+        let views = state.view_service().prune(before).await?;
+        writeln!(out, "Deleted {} views", views)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{instrument, Instrument};
+
+use crate::core::share::{InvalidSecret, ShareNotFound};
+use crate::database::query_span;
+use crate::models::{DailyViews, ReferrerViews, ShareStatsResponse};
+
+/// Longest referrer stored; longer values are truncated.
+const MAX_REFERRER_LEN: usize = 512;
+
+/// Number of referrers reported in stats.
+const TOP_REFERRERS: i64 = 10;
+
+/// Analytics settings, configured in the `[analytics]` section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnalyticsConfig {
+    /// Set to `false` to stop recording share views
+    pub enabled: bool,
+    /// Salt for visitor IP hashes; without it a random salt is used and unique visitor
+    /// counts restart whenever the server does
+    pub ip_hash_salt: Option<String>,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ip_hash_salt: None,
+        }
+    }
+}
+
+/// What was viewed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewKind {
+    /// The rendered share page
+    Page,
+    /// Share data fetched through the API
+    Data,
+}
+
+impl ViewKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Page => "page",
+            Self::Data => "data",
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ViewTotals {
+    total_views: i64,
+    unique_visitors: i64,
+    page_views: i64,
+    data_fetches: i64,
+    first_viewed_at: Option<DateTime<Utc>>,
+    last_viewed_at: Option<DateTime<Utc>>,
+}
+
+/// Records share views and reports per-share statistics.
+pub struct ViewService {
+    pool: PgPool,
+    salt: Arc<str>,
+}
+
+impl ViewService {
+    pub fn new(pool: PgPool, salt: Arc<str>) -> Self {
+        Self { pool, salt }
+    }
+
+    /// Record a view. Only a salted hash of `ip` is stored, scoped to the share so
+    /// visitors cannot be correlated across shares.
+    #[instrument(name = "views.record", skip_all, fields(share_id = %share_id, kind = kind.as_str()))]
+    pub async fn record(
+        &self,
+        share_id: &str,
+        kind: ViewKind,
+        ip: &str,
+        user_agent: Option<&str>,
+        referrer: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO share_views (share_id, kind, viewed_at, ip_hash, user_agent_class, referrer)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(share_id)
+        .bind(kind.as_str())
+        .bind(Utc::now())
+        .bind(self.hash_ip(share_id, ip))
+        .bind(classify_user_agent(user_agent.unwrap_or("")))
+        .bind(referrer.and_then(normalize_referrer))
+        .execute(&self.pool)
+        .instrument(query_span("INSERT", "share_views"))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete views recorded before `before`. Returns the number of views deleted.
+    #[instrument(name = "views.prune", skip_all)]
+    pub async fn prune(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM share_views WHERE viewed_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .instrument(query_span("DELETE", "share_views"))
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// View statistics for a share, with daily counts for the last `days` days.
+    #[instrument(name = "views.stats", skip_all, fields(share_id = %share_id))]
+    pub async fn stats(&self, share_id: &str, secret: &str, days: u32) -> Result<ShareStatsResponse> {
+        let stored_secret: Option<String> =
+            sqlx::query_scalar("SELECT secret FROM shares WHERE id = $1")
+                .bind(share_id)
+                .fetch_optional(&self.pool)
+                .instrument(query_span("SELECT", "shares"))
+                .await?;
+        let stored_secret = stored_secret.ok_or_else(|| ShareNotFound(share_id.to_string()))?;
+
+        if stored_secret != secret {
+            return Err(InvalidSecret(share_id.to_string()).into());
+        }
+
+        let totals = sqlx::query_as::<_, ViewTotals>(
+            r#"
+            SELECT COUNT(*) AS total_views,
+                   COUNT(DISTINCT ip_hash) AS unique_visitors,
+                   COUNT(*) FILTER (WHERE kind = 'page') AS page_views,
+                   COUNT(*) FILTER (WHERE kind = 'data') AS data_fetches,
+                   MIN(viewed_at) AS first_viewed_at,
+                   MAX(viewed_at) AS last_viewed_at
+            FROM share_views
+            WHERE share_id = $1
+            "#,
+        )
+        .bind(share_id)
+        .fetch_one(&self.pool)
+        .instrument(query_span("SELECT", "share_views"))
+        .await?;
+
+        let since = Utc::now() - Duration::days(i64::from(days));
+        let daily: Vec<(NaiveDate, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT (viewed_at AT TIME ZONE 'UTC')::date AS day,
+                   COUNT(*),
+                   COUNT(DISTINCT ip_hash)
+            FROM share_views
+            WHERE share_id = $1 AND viewed_at >= $2
+            GROUP BY day
+            ORDER BY day
+            "#,
+        )
+        .bind(share_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .instrument(query_span("SELECT", "share_views"))
+        .await?;
+
+        let user_agents: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT user_agent_class, COUNT(*) FROM share_views WHERE share_id = $1 GROUP BY user_agent_class",
+        )
+        .bind(share_id)
+        .fetch_all(&self.pool)
+        .instrument(query_span("SELECT", "share_views"))
+        .await?;
+
+        let referrers: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT referrer, COUNT(*) AS views
+            FROM share_views
+            WHERE share_id = $1 AND referrer IS NOT NULL
+            GROUP BY referrer
+            ORDER BY views DESC, referrer
+            LIMIT $2
+            "#,
+        )
+        .bind(share_id)
+        .bind(TOP_REFERRERS)
+        .fetch_all(&self.pool)
+        .instrument(query_span("SELECT", "share_views"))
+        .await?;
+
+        Ok(ShareStatsResponse {
+            total_views: totals.total_views,
+            unique_visitors: totals.unique_visitors,
+            page_views: totals.page_views,
+            data_fetches: totals.data_fetches,
+            first_viewed_at: totals.first_viewed_at,
+            last_viewed_at: totals.last_viewed_at,
+            daily: daily
+                .into_iter()
+                .map(|(date, views, unique_visitors)| DailyViews {
+                    date,
+                    views,
+                    unique_visitors,
+                })
+                .collect(),
+            user_agents: user_agents.into_iter().collect::<BTreeMap<_, _>>(),
+            referrers: referrers
+                .into_iter()
+                .map(|(referrer, views)| ReferrerViews { referrer, views })
+                .collect(),
+        })
+    }
+
+    fn hash_ip(&self, share_id: &str, ip: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update([0]);
+        hasher.update(share_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(ip.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Coarse client class: `bot`, `cli`, `mobile`, `desktop` or `unknown`.
+pub fn classify_user_agent(user_agent: &str) -> &'static str {
+    let ua = user_agent.to_ascii_lowercase();
+
+    if ua.trim().is_empty() || ua == "unknown" {
+        "unknown"
+    } else if ["bot", "crawler", "spider", "slurp", "preview", "facebookexternalhit"]
+        .iter()
+        .any(|marker| ua.contains(marker))
+    {
+        "bot"
+    } else if ["curl", "wget", "httpie", "python-requests", "go-http-client", "opencode", "reqwest"]
+        .iter()
+        .any(|marker| ua.contains(marker))
+    {
+        "cli"
+    } else if ["mobile", "android", "iphone", "ipad"]
+        .iter()
+        .any(|marker| ua.contains(marker))
+    {
+        "mobile"
+    } else if ua.contains("mozilla") {
+        "desktop"
+    } else {
+        "unknown"
+    }
+}
+
+/// Keep only the scheme, host and path of an http(s) referrer, dropping query strings
+/// and fragments that may carry tokens.
+pub fn normalize_referrer(referrer: &str) -> Option<String> {
+    let referrer = referrer.trim();
+    if !(referrer.starts_with("http://") || referrer.starts_with("https://")) {
+        return None;
+    }
+
+    let end = referrer.find(['?', '#']).unwrap_or(referrer.len());
+    let mut normalized = referrer[..end].to_string();
+    if normalized.len() > MAX_REFERRER_LEN {
+        let mut cut = MAX_REFERRER_LEN;
+        while !normalized.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        normalized.truncate(cut);
+    }
+
+    Some(normalized)
+}
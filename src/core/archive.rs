@@ -0,0 +1,245 @@
+use anyhow::Result;
+use async_compression::tokio::{bufread::GzipDecoder, write::GzipEncoder};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{instrument, Instrument};
+
+use crate::database::query_span;
+use crate::models::Share;
+
+/// Identifies archive files, written in the first line.
+pub const ARCHIVE_FORMAT: &str = "opencode-share-archive";
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Shares read from the database per query while exporting.
+const EXPORT_BATCH_SIZE: i64 = 100;
+
+/// Returned (wrapped in `anyhow::Error`) when an archive cannot be read.
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid archive: {0}")]
+pub struct InvalidArchive(pub String);
+
+/// First line of an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveHeader {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// One line of an archive after the header: a share with everything stored for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedShare {
+    pub share: Share,
+    #[serde(default)]
+    pub views: Vec<ArchivedView>,
+}
+
+/// A recorded share view, without its database ID.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArchivedView {
+    #[serde(skip)]
+    pub share_id: String,
+    pub kind: String,
+    pub viewed_at: DateTime<Utc>,
+    pub ip_hash: String,
+    pub user_agent_class: String,
+    pub referrer: Option<String>,
+}
+
+/// What an import did with the shares in an archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    /// Shares that did not exist yet
+    pub created: usize,
+    /// Existing shares replaced by a newer archived version
+    pub updated: usize,
+    /// Existing shares at least as new as the archived version
+    pub skipped: usize,
+    /// Views copied along with created shares
+    pub views: usize,
+}
+
+/// Write every share and its views to `writer` as gzip-compressed NDJSON: an
+/// [`ArchiveHeader`] line followed by one [`ArchivedShare`] per line, ordered by ID.
+///
+/// Returns the number of shares written.
+#[instrument(name = "archive.export", skip_all)]
+pub async fn export_archive<W: AsyncWrite + Unpin>(pool: &PgPool, writer: W) -> Result<usize> {
+    let mut out = GzipEncoder::new(writer);
+    write_line(
+        &mut out,
+        &ArchiveHeader {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+        },
+    )
+    .await?;
+
+    let mut count = 0;
+    let mut after = String::new();
+    loop {
+        let shares = sqlx::query_as::<_, Share>(
+            r#"
+            SELECT id, secret, session_id, data, storage_bytes, redactions, created_at, updated_at
+            FROM shares
+            WHERE id > $1
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind(&after)
+        .bind(EXPORT_BATCH_SIZE)
+        .fetch_all(pool)
+        .instrument(query_span("SELECT", "shares"))
+        .await?;
+        let Some(last) = shares.last() else {
+            break;
+        };
+        after = last.id.clone();
+
+        let ids: Vec<&str> = shares.iter().map(|share| share.id.as_str()).collect();
+        let mut views: HashMap<String, Vec<ArchivedView>> = HashMap::new();
+        let rows = sqlx::query_as::<_, ArchivedView>(
+            r#"
+            SELECT share_id, kind, viewed_at, ip_hash, user_agent_class, referrer
+            FROM share_views
+            WHERE share_id = ANY($1)
+            ORDER BY viewed_at, id
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(pool)
+        .instrument(query_span("SELECT", "share_views"))
+        .await?;
+        for view in rows {
+            views.entry(view.share_id.clone()).or_default().push(view);
+        }
+
+        for share in shares {
+            let views = views.remove(&share.id).unwrap_or_default();
+            write_line(&mut out, &ArchivedShare { share, views }).await?;
+            count += 1;
+        }
+    }
+
+    out.shutdown().await?;
+    Ok(count)
+}
+
+/// Load an archive written by [`export_archive`].
+///
+/// Importing is idempotent: a share is inserted if missing and replaced only when the
+/// archived copy has a newer `updated_at`, so the same archive can be imported again
+/// after an interruption. Views are copied only for newly created shares. Each share is
+/// imported in its own transaction.
+#[instrument(name = "archive.import", skip_all)]
+pub async fn import_archive<R: AsyncRead + Unpin>(pool: &PgPool, reader: R) -> Result<ImportSummary> {
+    let mut lines = BufReader::new(GzipDecoder::new(BufReader::new(reader))).lines();
+
+    let invalid = |e: std::io::Error| InvalidArchive(e.to_string());
+
+    let header = lines
+        .next_line()
+        .await
+        .map_err(invalid)?
+        .ok_or_else(|| InvalidArchive("empty archive".to_string()))?;
+    let header: ArchiveHeader = serde_json::from_str(&header)
+        .map_err(|e| InvalidArchive(format!("header: {}", e)))?;
+    if header.format != ARCHIVE_FORMAT || header.version != ARCHIVE_VERSION {
+        return Err(InvalidArchive(format!(
+            "unsupported format {} version {}",
+            header.format, header.version
+        ))
+        .into());
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut line_number = 1;
+    while let Some(line) = lines.next_line().await.map_err(invalid)? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let archived: ArchivedShare = serde_json::from_str(&line)
+            .map_err(|e| InvalidArchive(format!("line {}: {}", line_number, e)))?;
+        import_share(pool, archived, &mut summary).await?;
+    }
+
+    Ok(summary)
+}
+
+async fn import_share(pool: &PgPool, archived: ArchivedShare, summary: &mut ImportSummary) -> Result<()> {
+    let ArchivedShare { share, views } = archived;
+    let mut tx = pool.begin().await?;
+
+    // `xmax = 0` only for rows this statement inserted; no row at all means the
+    // existing share is at least as new and was left alone
+    let inserted: Option<bool> = sqlx::query_scalar(
+        r#"
+        INSERT INTO shares (id, secret, session_id, data, storage_bytes, redactions, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (id) DO UPDATE
+        SET secret = EXCLUDED.secret,
+            session_id = EXCLUDED.session_id,
+            data = EXCLUDED.data,
+            storage_bytes = EXCLUDED.storage_bytes,
+            redactions = EXCLUDED.redactions,
+            created_at = EXCLUDED.created_at,
+            updated_at = EXCLUDED.updated_at
+        WHERE shares.updated_at < EXCLUDED.updated_at
+        RETURNING (xmax = 0)
+        "#,
+    )
+    .bind(&share.id)
+    .bind(&share.secret)
+    .bind(&share.session_id)
+    .bind(&share.data)
+    .bind(share.storage_bytes)
+    .bind(&share.redactions)
+    .bind(share.created_at)
+    .bind(share.updated_at)
+    .fetch_optional(&mut *tx)
+    .instrument(query_span("INSERT", "shares"))
+    .await?;
+
+    match inserted {
+        Some(true) => {
+            for view in &views {
+                sqlx::query(
+                    r#"
+                    INSERT INTO share_views (share_id, kind, viewed_at, ip_hash, user_agent_class, referrer)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    "#,
+                )
+                .bind(&share.id)
+                .bind(&view.kind)
+                .bind(view.viewed_at)
+                .bind(&view.ip_hash)
+                .bind(&view.user_agent_class)
+                .bind(&view.referrer)
+                .execute(&mut *tx)
+                .instrument(query_span("INSERT", "share_views"))
+                .await?;
+            }
+            summary.created += 1;
+            summary.views += views.len();
+        }
+        Some(false) => summary.updated += 1,
+        None => summary.skipped += 1,
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn write_line<W: AsyncWrite + Unpin, T: Serialize>(out: &mut W, value: &T) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    out.write_all(&line).await?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use minijinja::context;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::core::meta::ShareMeta;
+use crate::core::render::render_transcript;
+use crate::core::transcript::Transcript;
+use crate::templates::{Templates, EXPORT_TEMPLATE};
+
+/// Render a share as a single HTML file with `share.css` and `share.js` inlined from
+/// `static_dir`, so it renders offline, e.g. when attached to a ticket.
+pub async fn render_html_export(
+    templates: &Templates,
+    static_dir: &Path,
+    share_id: &str,
+    items: &[Value],
+    base_url: Option<&str>,
+) -> Result<String> {
+    let transcript = Transcript::from_items(items);
+    let meta = ShareMeta::new(&transcript, share_id, base_url);
+
+    let (css, js) = tokio::try_join!(
+        tokio::fs::read_to_string(static_dir.join("share.css")),
+        tokio::fs::read_to_string(static_dir.join("share.js")),
+    )
+    .with_context(|| format!("Failed to read static assets from {}", static_dir.display()))?;
+
+    templates.render(
+        EXPORT_TEMPLATE,
+        context! {
+            share_id => share_id,
+            content => render_transcript(&transcript),
+            meta => meta,
+            css => inline_safe(&css),
+            js => inline_safe(&js),
+        },
+    )
+}
+
+/// Keep inlined CSS or JavaScript from closing its `<style>`/`<script>` element early.
+fn inline_safe(source: &str) -> String {
+    source.replace("</", "<\\/")
+}
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::core::transcript::str_field;
+
+/// A session as written by `opencode export <sessionID>`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionExport {
+    /// Session info: `id`, `title`, `version`, `time`, ...
+    pub info: Value,
+    #[serde(default)]
+    pub messages: Vec<ExportedMessage>,
+}
+
+/// A message of an export together with its parts.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportedMessage {
+    pub info: Value,
+    #[serde(default)]
+    pub parts: Vec<Value>,
+}
+
+impl SessionExport {
+    /// ID of the exported session; also used as the share ID.
+    pub fn session_id(&self) -> Option<&str> {
+        Some(str_field(&self.info, "id")).filter(|id| !id.is_empty())
+    }
+
+    /// Translate the export into the items opencode syncs live, keyed so a later sync of
+    /// the same session replaces them instead of duplicating them:
+    /// `session`, `message/<messageID>` and `part/<partID>`.
+    ///
+    /// Missing `sessionID`/`messageID` back-references are filled in from the export's
+    /// nesting so the share page can group messages and parts.
+    pub fn into_items(self) -> Vec<Value> {
+        let session_id = str_field(&self.info, "id").to_string();
+        let mut items = vec![json!({ "_key": "session", "type": "session", "data": self.info })];
+
+        for message in self.messages {
+            let mut info = message.info;
+            set_missing(&mut info, "sessionID", &session_id);
+            let message_id = str_field(&info, "id").to_string();
+
+            items.push(json!({
+                "_key": format!("message/{}", message_id),
+                "type": "message",
+                "data": info,
+            }));
+
+            for mut part in message.parts {
+                set_missing(&mut part, "messageID", &message_id);
+                set_missing(&mut part, "sessionID", &session_id);
+                let key = match part.get("id").and_then(Value::as_str) {
+                    Some(id) => format!("part/{}", id),
+                    None => format!("part/{}/{}", message_id, items.len()),
+                };
+
+                items.push(json!({ "_key": key, "type": "part", "data": part }));
+            }
+        }
+
+        items
+    }
+}
+
+fn set_missing(object: &mut Value, field: &str, value: &str) {
+    if let Some(object) = object.as_object_mut() {
+        object
+            .entry(field)
+            .or_insert_with(|| Value::String(value.to_string()));
+    }
+}
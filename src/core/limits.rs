@@ -0,0 +1,37 @@
+use serde::Deserialize;
+
+/// Size limits applied to incoming share data, configured in the `[limits]` section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Maximum HTTP request body size
+    pub max_body_bytes: usize,
+    /// Maximum number of items in a single sync request
+    pub max_sync_items: usize,
+    /// Maximum serialized size of a single item
+    pub max_item_bytes: usize,
+    /// Maximum total serialized size of a share's data
+    pub max_share_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 16 * 1024 * 1024,
+            max_sync_items: 1000,
+            max_item_bytes: 4 * 1024 * 1024,
+            max_share_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Returned (wrapped in `anyhow::Error`) when incoming data exceeds a configured limit.
+#[derive(Debug, thiserror::Error)]
+pub enum LimitError {
+    #[error("Too many items in sync: {count} (max {max})")]
+    TooManyItems { count: usize, max: usize },
+    #[error("Item too large: {size} bytes (max {max})")]
+    ItemTooLarge { size: usize, max: usize },
+    #[error("Share storage quota exceeded: {size} bytes (max {max})")]
+    QuotaExceeded { size: usize, max: usize },
+}
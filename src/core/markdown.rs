@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::core::patch::FileChange;
+use crate::core::render::{token_usage, truthy, OUTPUT_COLLAPSE_LINES};
+use crate::core::transcript::{created_at, str_field, ToolCall, Transcript};
+
+/// Render a share's conversation as Markdown for pasting into PRs and docs.
+///
+/// Follows the share page layout: session header, then each message with its text,
+/// reasoning collapsed in `<details>`, tool calls with fenced input and output, step
+/// markers with token summaries, and finally the changed files as unified diffs.
+pub fn render_markdown(transcript: &Transcript) -> String {
+    let Some(session) = &transcript.session else {
+        return "# Share Data Missing\n\nThe share data is incomplete or corrupted.\n".to_string();
+    };
+
+    let messages = transcript.messages();
+    let mut md = String::new();
+
+    let title = str_field(session, "title");
+    let _ = writeln!(md, "# {}\n", if title.is_empty() { "Opencode Share" } else { title });
+
+    let mut meta = vec![format!(
+        "v{}",
+        session.get("version").and_then(Value::as_str).unwrap_or("1.0.0")
+    )];
+    if let Some(created) = created_at(session).and_then(timestamp) {
+        meta.push(created.format("%Y-%m-%d").to_string());
+    }
+    if let Some(directory) = session.get("directory").and_then(Value::as_str) {
+        meta.push(format!("`{}`", directory));
+    }
+    if let Some(files) = session.pointer("/summary/files").filter(|files| truthy(files)) {
+        meta.push(format!("{} files", scalar(files)));
+    }
+    let _ = writeln!(md, "{}\n", meta.join(" • "));
+    let _ = writeln!(
+        md,
+        "**Messages:** {} • **Session:** `{}`\n",
+        messages.len(),
+        str_field(session, "id")
+    );
+
+    for message in messages {
+        md.push_str("---\n\n");
+        message_markdown(&mut md, transcript, message);
+    }
+
+    if !transcript.diffs.is_empty() {
+        let _ = writeln!(md, "---\n\n## {} Files Changed\n", transcript.diffs.len());
+        for diff in &transcript.diffs {
+            let change = FileChange::from_value(diff, transcript.root());
+            let _ = writeln!(
+                md,
+                "### `{}`\n\n{}",
+                change.path,
+                fenced("diff", &change.unified_diff())
+            );
+        }
+    }
+
+    md.truncate(md.trim_end().len());
+    md.push('\n');
+    md
+}
+
+fn message_markdown(md: &mut String, transcript: &Transcript, message: &Value) {
+    let role = message.get("role").and_then(Value::as_str).unwrap_or("unknown");
+
+    let mut heading = vec![if role == "user" { "👤 User" } else { "🤖 Assistant" }.to_string()];
+    if let Some(model) = message.get("modelID").and_then(Value::as_str) {
+        heading.push(format!("`{}`", model));
+    }
+    if let Some(created) = created_at(message).and_then(timestamp) {
+        heading.push(created.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+    }
+    let input = message.pointer("/tokens/input").filter(|tokens| truthy(tokens));
+    let output = message.pointer("/tokens/output").filter(|tokens| truthy(tokens));
+    if input.is_some() || output.is_some() {
+        let mut tokens = Vec::new();
+        if let Some(input) = input {
+            tokens.push(format!("📥 {}", scalar(input)));
+        }
+        if let Some(output) = output {
+            tokens.push(format!("📤 {}", scalar(output)));
+        }
+        heading.push(tokens.join(" "));
+    }
+    let _ = writeln!(md, "## {}\n", heading.join(" · "));
+
+    if let Some(content) = message.get("content").and_then(Value::as_str) {
+        if !content.is_empty() {
+            let _ = writeln!(md, "{}\n", content.trim_end());
+            return;
+        }
+    }
+
+    let parts = transcript.parts(str_field(message, "id"));
+    if parts.is_empty() {
+        let _ = writeln!(
+            md,
+            "*[{} message via {} - {}]*\n",
+            role,
+            message.get("modelID").and_then(Value::as_str).unwrap_or("unknown model"),
+            message.get("finish").and_then(Value::as_str).unwrap_or("unknown"),
+        );
+        return;
+    }
+
+    let start = md.len();
+    for part in parts {
+        match str_field(part, "type") {
+            "text" => {
+                let text = str_field(part, "text").trim_end();
+                if !text.is_empty() {
+                    let _ = writeln!(md, "{}\n", text);
+                }
+            }
+            "reasoning" => {
+                let text = str_field(part, "text").trim();
+                if !text.is_empty() {
+                    let _ = writeln!(
+                        md,
+                        "<details>\n<summary>🤔 Thinking Process</summary>\n\n{}\n</details>\n",
+                        fenced("", text)
+                    );
+                }
+            }
+            "tool" | "tool-call" => {
+                if let Some(state) = part.get("state") {
+                    tool_markdown(md, state, part.get("tool").and_then(Value::as_str));
+                }
+            }
+            "step-start" => md.push_str("> 🚀 Step Started\n\n"),
+            "step-finish" => {
+                let tokens = part.get("tokens").map(token_usage).unwrap_or_default();
+                if tokens.is_empty() {
+                    md.push_str("> ✅ Step Completed\n\n");
+                } else {
+                    let _ = writeln!(md, "> ✅ Step Completed {}\n", tokens);
+                }
+            }
+            other => {
+                let text = str_field(part, "text");
+                if !text.is_empty() {
+                    let _ = writeln!(md, "📝 **{}:**\n{}\n", other, text.trim_end());
+                }
+            }
+        }
+    }
+
+    if md.len() == start {
+        let _ = writeln!(md, "*[{} message - no content]*\n", role);
+    }
+}
+
+fn tool_markdown(md: &mut String, state: &Value, tool: Option<&str>) {
+    let call = ToolCall::from_state(state, tool);
+
+    let _ = writeln!(
+        md,
+        "**🔧 {}{}** · `{}`\n",
+        call.tool,
+        if call.title.is_empty() { String::new() } else { format!(" - {}", call.title) },
+        call.status
+    );
+
+    if let Some(input) = state.get("input").filter(|input| truthy(input)) {
+        let input = if let Some(input) = input.as_str() {
+            fenced("", input)
+        } else if let Some(command) = input.get("command").and_then(Value::as_str) {
+            fenced("bash", &format!("$ {}", command))
+        } else {
+            fenced("json", &serde_json::to_string_pretty(input).unwrap_or_default())
+        };
+        let _ = writeln!(md, "Input:\n\n{}", input);
+    }
+
+    let output = str_field(state, "output").trim();
+    if !output.is_empty() {
+        let lines = output.lines().count();
+        if lines > OUTPUT_COLLAPSE_LINES {
+            let _ = writeln!(
+                md,
+                "<details>\n<summary>Output ({} lines)</summary>\n\n{}\n</details>\n",
+                lines,
+                fenced("", output)
+            );
+        } else {
+            let _ = writeln!(md, "Output:\n\n{}", fenced("", output));
+        }
+    }
+
+    let mut metadata = Vec::new();
+    if let Some(exit) = state.pointer("/metadata/exit").filter(|exit| !exit.is_null()) {
+        metadata.push(format!("Exit: {}", scalar(exit)));
+    }
+    if let Some(duration) = call.duration {
+        metadata.push(format!("⏱ {}ms", duration));
+    }
+    if !metadata.is_empty() {
+        let _ = writeln!(md, "*{}*\n", metadata.join(" • "));
+    }
+}
+
+/// A fenced code block, using a fence longer than any backtick run in `text`.
+fn fenced(lang: &str, text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n", text.trim_end_matches('\n'))
+}
+
+/// Display a JSON scalar the way JavaScript would interpolate it.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn timestamp(millis: i64) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_timestamp_millis(millis)
+}
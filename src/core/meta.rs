@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+use crate::core::og_image::{OG_IMAGE_HEIGHT, OG_IMAGE_WIDTH};
+use crate::core::transcript::Transcript;
+
+/// Title used when a share has no session title.
+const DEFAULT_TITLE: &str = "Opencode Share";
+
+/// Longest first-prompt excerpt included in descriptions.
+const EXCERPT_CHARS: usize = 160;
+
+/// Open Graph and Twitter card metadata for a share page.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareMeta {
+    pub title: String,
+    pub description: String,
+    /// Absolute share URL; `None` when the public base URL cannot be determined
+    pub url: Option<String>,
+    /// Absolute URL of the generated preview image (`/share/:id/og.png`)
+    pub image: Option<String>,
+    /// Absolute URL of the oEmbed endpoint, advertised with a discovery link
+    pub oembed_url: Option<String>,
+    pub image_width: u32,
+    pub image_height: u32,
+}
+
+impl ShareMeta {
+    /// Describe a share from its stored items, e.g. title "Fix the parser" and description
+    /// "claude-sonnet · 12 messages — Why does the parser fail on…".
+    ///
+    /// `base_url` is the public base URL used to make the share and image URLs absolute.
+    pub fn new(transcript: &Transcript, share_id: &str, base_url: Option<&str>) -> Self {
+        let title = transcript
+            .title()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or(DEFAULT_TITLE)
+            .to_string();
+
+        let count = transcript.messages().len();
+        let mut summary = vec![format!(
+            "{} message{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        )];
+        if let Some(model) = transcript.model() {
+            summary.insert(0, model.to_string());
+        }
+
+        let mut description = summary.join(" · ");
+        if let Some(prompt) = transcript.first_prompt() {
+            description.push_str(" — ");
+            description.push_str(&excerpt(&prompt, EXCERPT_CHARS));
+        }
+
+        Self {
+            title,
+            description,
+            url: base_url.map(|base| format!("{}/share/{}", base, share_id)),
+            image: base_url.map(|base| format!("{}/share/{}/og.png", base, share_id)),
+            oembed_url: base_url.map(|base| format!("{}/oembed", base)),
+            image_width: OG_IMAGE_WIDTH,
+            image_height: OG_IMAGE_HEIGHT,
+        }
+    }
+}
+
+/// Collapse whitespace and cut `text` to at most `max` characters, ending with `…` if cut.
+pub fn excerpt(text: &str, max: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max {
+        return collapsed;
+    }
+
+    let cut: String = collapsed.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
@@ -1 +1,14 @@
-pub mod share;
\ No newline at end of file
+pub mod analytics;
+pub mod archive;
+pub mod export;
+pub mod import;
+pub mod limits;
+pub mod markdown;
+pub mod meta;
+pub mod og_image;
+pub mod patch;
+pub mod public_url;
+pub mod redact;
+pub mod render;
+pub mod share;
+pub mod transcript;
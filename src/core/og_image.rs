@@ -0,0 +1,216 @@
+use anyhow::{anyhow, Result};
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use resvg::{tiny_skia, usvg};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::core::meta::excerpt;
+use crate::core::render::escape_html;
+use crate::core::transcript::Transcript;
+
+/// Size recommended for Open Graph and Twitter `summary_large_image` cards.
+pub const OG_IMAGE_WIDTH: u32 = 1200;
+pub const OG_IMAGE_HEIGHT: u32 = 630;
+
+/// Number of rendered images kept in memory.
+const CACHE_CAPACITY: usize = 256;
+
+const FONT_FAMILY: &str = "DejaVu Sans";
+const FONT_REGULAR: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const FONT_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// Title layout: characters per line at the title font size, and lines shown.
+const TITLE_LINE_CHARS: usize = 28;
+const TITLE_MAX_LINES: usize = 3;
+
+/// Renders per-share social preview images and caches them by share revision.
+///
+/// Images are drawn as SVG and rasterized with resvg using the bundled DejaVu fonts,
+/// so output does not depend on fonts installed on the host.
+pub struct OgImageRenderer {
+    fontdb: Arc<usvg::fontdb::Database>,
+    cache: Mutex<HashMap<String, CachedImage>>,
+}
+
+struct CachedImage {
+    revision: DateTime<Utc>,
+    png: Bytes,
+    last_used: Instant,
+}
+
+impl Default for OgImageRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OgImageRenderer {
+    pub fn new() -> Self {
+        let mut fontdb = usvg::fontdb::Database::new();
+        fontdb.load_font_data(FONT_REGULAR.to_vec());
+        fontdb.load_font_data(FONT_BOLD.to_vec());
+
+        Self {
+            fontdb: Arc::new(fontdb),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached PNG for `share_id` if it was rendered at `revision`.
+    pub fn cached(&self, share_id: &str, revision: DateTime<Utc>) -> Option<Bytes> {
+        let mut cache = self.cache.lock().unwrap();
+        let entry = cache.get_mut(share_id).filter(|entry| entry.revision == revision)?;
+        entry.last_used = Instant::now();
+        Some(entry.png.clone())
+    }
+
+    /// Render the preview image for a share and cache it under `revision`.
+    pub fn render(
+        &self,
+        share_id: &str,
+        revision: DateTime<Utc>,
+        transcript: &Transcript,
+    ) -> Result<Bytes> {
+        let png = Bytes::from(self.render_png(&preview_svg(transcript))?);
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= CACHE_CAPACITY && !cache.contains_key(share_id) {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            share_id.to_string(),
+            CachedImage {
+                revision,
+                png: png.clone(),
+                last_used: Instant::now(),
+            },
+        );
+
+        Ok(png)
+    }
+
+    fn render_png(&self, svg: &str) -> Result<Vec<u8>> {
+        let options = usvg::Options {
+            font_family: FONT_FAMILY.to_string(),
+            fontdb: self.fontdb.clone(),
+            ..usvg::Options::default()
+        };
+        let tree = usvg::Tree::from_str(svg, &options)?;
+
+        let mut pixmap = tiny_skia::Pixmap::new(OG_IMAGE_WIDTH, OG_IMAGE_HEIGHT)
+            .ok_or_else(|| anyhow!("Failed to allocate preview image"))?;
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+        Ok(pixmap.encode_png()?)
+    }
+}
+
+/// SVG source of a share's preview card: title, model and session totals.
+pub fn preview_svg(transcript: &Transcript) -> String {
+    let title = transcript
+        .title()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or("Untitled session");
+    let title_lines: String = wrap(title, TITLE_LINE_CHARS, TITLE_MAX_LINES)
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                r#"<tspan x="80" y="{}">{}</tspan>"#,
+                250 + i * 76,
+                escape_html(line)
+            )
+        })
+        .collect();
+
+    let usage = transcript.usage();
+    let messages = transcript.messages().len();
+    let files = transcript.files_changed();
+
+    let mut stats = vec![format!("{} message{}", messages, plural(messages))];
+    if usage.total_tokens() > 0 {
+        stats.push(format!("{} tokens", compact_number(usage.total_tokens())));
+    }
+    if usage.cost > 0.0 {
+        stats.push(format!("${:.2}", usage.cost));
+    }
+    if files > 0 {
+        stats.push(format!("{} file{} changed", files, plural(files)));
+    }
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#,
+            r##"<rect width="100%" height="100%" fill="#0f1115"/>"##,
+            r##"<rect x="0" y="0" width="12" height="{height}" fill="#4f8cff"/>"##,
+            r##"<text x="80" y="120" font-size="36" font-weight="bold" fill="#9aa0a6">opencode</text>"##,
+            r##"<text font-size="60" font-weight="bold" fill="#ffffff">{title}</text>"##,
+            r##"<text x="80" y="500" font-size="34" fill="#8ab4f8">{model}</text>"##,
+            r##"<text x="80" y="560" font-size="30" fill="#c4c7c5">{stats}</text>"##,
+            "</svg>",
+        ),
+        width = OG_IMAGE_WIDTH,
+        height = OG_IMAGE_HEIGHT,
+        title = title_lines,
+        model = escape_html(transcript.model().unwrap_or("")),
+        stats = escape_html(&stats.join("  ·  ")),
+    )
+}
+
+/// Word-wrap `text` into at most `max_lines` lines of about `width` characters, marking
+/// truncation with `…`.
+fn wrap(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let word = excerpt(word, width);
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        let last = &mut lines[max_lines - 1];
+        if last.chars().count() >= width {
+            *last = last.chars().take(width - 1).collect();
+        }
+        last.push('…');
+    }
+
+    lines
+}
+
+/// `1234` -> `1.2k`, `2500000` -> `2.5M`.
+fn compact_number(n: i64) -> String {
+    match n {
+        n if n >= 1_000_000 => format!("{:.1}M", n as f64 / 1_000_000.0),
+        n if n >= 1_000 => format!("{:.1}k", n as f64 / 1_000.0),
+        n => n.to_string(),
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
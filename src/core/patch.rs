@@ -0,0 +1,162 @@
+use serde_json::Value;
+use similar::TextDiff;
+use std::fmt::Write;
+
+use crate::core::transcript::str_field;
+
+/// A file's change as stored in a `session_diff` item.
+#[derive(Debug, Clone, Copy)]
+pub struct FileChange<'a> {
+    pub path: &'a str,
+    pub before: &'a str,
+    pub after: &'a str,
+    added: bool,
+    deleted: bool,
+}
+
+impl<'a> FileChange<'a> {
+    /// Read a `{ "file", "before", "after", "status" }` entry. Paths are made relative to
+    /// `root`, the session's working directory, so the patch applies from the repository root.
+    ///
+    /// A file counts as added or deleted when `status` says so, or without a `status` when
+    /// `before` or `after` is missing or `null`; empty content is an empty file.
+    pub fn from_value(diff: &'a Value, root: Option<&str>) -> Self {
+        let status = str_field(diff, "status");
+        let absent = |name: &str| diff.get(name).is_none_or(Value::is_null);
+        let (added, deleted) = if status.is_empty() {
+            (absent("before"), absent("after"))
+        } else {
+            (status == "added", status == "deleted")
+        };
+
+        Self {
+            path: normalize_path(str_field(diff, "file"), root),
+            before: str_field(diff, "before"),
+            after: str_field(diff, "after"),
+            added,
+            deleted,
+        }
+    }
+
+    /// The file did not exist before the session.
+    pub fn is_added(&self) -> bool {
+        self.added
+    }
+
+    /// The file was removed during the session.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    /// Unified diff of the change with `---`/`+++` headers, empty when nothing changed.
+    pub fn unified_diff(&self) -> String {
+        let diff = TextDiff::from_lines(self.before, self.after);
+        let mut hunks = String::new();
+
+        for hunk in diff.unified_diff().iter_hunks() {
+            let _ = writeln!(hunks, "{}", hunk.header());
+            for change in hunk.iter_changes() {
+                let _ = write!(hunks, "{}{}", change.tag(), change.value());
+                if change.missing_newline() {
+                    hunks.push_str("\n\\ No newline at end of file\n");
+                }
+            }
+        }
+        if hunks.is_empty() {
+            return hunks;
+        }
+
+        let old = if self.is_added() {
+            "/dev/null".to_string()
+        } else {
+            quote_path("a/", self.path)
+        };
+        let new = if self.is_deleted() {
+            "/dev/null".to_string()
+        } else {
+            quote_path("b/", self.path)
+        };
+        format!("--- {}\n+++ {}\n{}", old, new, hunks)
+    }
+}
+
+/// Convert the files of a `session_diff` item into a patch that `git apply` accepts.
+///
+/// Files whose contents did not change are left out. Paths are made relative to `root`.
+pub fn session_patch(diffs: &[Value], root: Option<&str>) -> String {
+    let mut patch = String::new();
+
+    for diff in diffs {
+        let change = FileChange::from_value(diff, root);
+        let body = change.unified_diff();
+        if change.path.is_empty() || body.is_empty() {
+            continue;
+        }
+
+        let _ = writeln!(
+            patch,
+            "diff --git {} {}",
+            quote_path("a/", change.path),
+            quote_path("b/", change.path)
+        );
+        if change.is_added() {
+            patch.push_str("new file mode 100644\n");
+        } else if change.is_deleted() {
+            patch.push_str("deleted file mode 100644\n");
+        }
+        patch.push_str(&body);
+    }
+
+    patch
+}
+
+/// After-content of `path` in a `session_diff` item; `None` if the file is not part of it
+/// or was deleted. `path` is relative to `root`, like the paths in [`session_patch`].
+pub fn file_after<'a>(diffs: &'a [Value], root: Option<&str>, path: &str) -> Option<&'a str> {
+    let path = normalize_path(path, root);
+    diffs
+        .iter()
+        .map(|diff| FileChange::from_value(diff, root))
+        .find(|change| !change.path.is_empty() && change.path == path)
+        .filter(|change| !change.is_deleted())
+        .map(|change| change.after)
+}
+
+fn normalize_path<'a>(path: &'a str, root: Option<&str>) -> &'a str {
+    let path = root
+        .and_then(|root| path.strip_prefix(root.trim_end_matches('/')))
+        .and_then(|relative| relative.strip_prefix('/'))
+        .unwrap_or(path);
+    let path = path.trim_start_matches('/');
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// `prefix` and `path` as git writes them in patch headers: as is, or C-style quoted with
+/// octal escapes when the path contains spaces, quotes, backslashes, control or non-ASCII
+/// characters.
+fn quote_path(prefix: &str, path: &str) -> String {
+    if !path.bytes().any(|b| b == b' ' || b == b'"' || b == b'\\' || !(0x20..0x7f).contains(&b)) {
+        return format!("{}{}", prefix, path);
+    }
+
+    let mut quoted = format!("\"{}", prefix);
+    for byte in path.bytes() {
+        match byte {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\x07' => quoted.push_str("\\a"),
+            b'\x08' => quoted.push_str("\\b"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\x0b' => quoted.push_str("\\v"),
+            b'\x0c' => quoted.push_str("\\f"),
+            b'\r' => quoted.push_str("\\r"),
+            b' '..=b'~' => quoted.push(byte as char),
+            _ => {
+                let _ = write!(quoted, "\\{:03o}", byte);
+            }
+        }
+    }
+    quoted.push('"');
+    quoted
+}
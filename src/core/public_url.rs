@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use axum::http::HeaderMap;
+
+/// Returned (wrapped in `anyhow::Error`) when a request's host is not in the allow-list.
+#[derive(Debug, thiserror::Error)]
+#[error("Host not allowed: {0}")]
+pub struct HostNotAllowed(pub String);
+
+/// How public share URLs are built.
+///
+/// When a base URL is configured it is always used. Otherwise the URL is derived from
+/// `Host`/`X-Forwarded-Host`, which must match an allowed host: entries without a port
+/// match any port and `*.` matches subdomains. No allowed hosts means any host is accepted.
+#[derive(Debug, Clone, Default)]
+pub struct PublicUrl {
+    base_url: Option<String>,
+    allowed_hosts: Vec<String>,
+}
+
+impl PublicUrl {
+    pub fn new(base_url: Option<&str>, allowed_hosts: Vec<String>) -> Result<Self> {
+        let base_url = match base_url.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                Some(url.trim_end_matches('/').to_string())
+            }
+            Some(url) => return Err(anyhow!("Invalid public base URL: {}", url)),
+            None => None,
+        };
+
+        let allowed_hosts = allowed_hosts
+            .into_iter()
+            .map(|h| h.trim().to_ascii_lowercase())
+            .filter(|h| !h.is_empty())
+            .collect();
+
+        Ok(Self {
+            base_url,
+            allowed_hosts,
+        })
+    }
+
+    /// The configured base URL, for links built outside of a request.
+    pub fn configured(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    /// Base URL (scheme and host, no trailing slash) for links served to this request.
+    pub fn base_url(&self, headers: &HeaderMap) -> Result<String> {
+        if let Some(base_url) = &self.base_url {
+            return Ok(base_url.clone());
+        }
+
+        let protocol = headers
+            .get("x-forwarded-proto")
+            .or_else(|| headers.get("x-forwarded-protocol"))
+            .and_then(|h| h.to_str().ok())
+            .filter(|p| *p == "http" || *p == "https")
+            .unwrap_or("https");
+
+        let host = headers
+            .get("x-forwarded-host")
+            .or_else(|| headers.get("host"))
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("localhost:3000");
+
+        if !self.is_host_allowed(host) {
+            return Err(HostNotAllowed(host.to_string()).into());
+        }
+
+        Ok(format!("{protocol}://{host}"))
+    }
+
+    fn is_host_allowed(&self, host: &str) -> bool {
+        // Reject anything that could smuggle a path, userinfo or whitespace into the URL
+        if host.is_empty()
+            || host
+                .chars()
+                .any(|c| matches!(c, '/' | '\\' | '@' | '?' | '#') || c.is_whitespace())
+        {
+            return false;
+        }
+
+        if self.allowed_hosts.is_empty() {
+            return true;
+        }
+
+        let host = host.to_ascii_lowercase();
+        let hostname = strip_port(&host);
+
+        self.allowed_hosts.iter().any(|allowed| {
+            // Entries without a port match the host on any port
+            let candidate = if strip_port(allowed) == allowed {
+                hostname
+            } else {
+                host.as_str()
+            };
+
+            match allowed.strip_prefix("*.") {
+                Some(domain) => candidate
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+                None => candidate == allowed.as_str(),
+            }
+        })
+    }
+}
+
+/// Strip a trailing `:port`, leaving bracketed IPv6 literals intact.
+fn strip_port(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((name, port))
+            if !port.is_empty()
+                && port.chars().all(|c| c.is_ascii_digit())
+                && (!name.contains(':') || name.ends_with(']')) =>
+        {
+            name
+        }
+        _ => host,
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Number of redactions per rule name.
+pub type RedactionCounts = BTreeMap<String, usize>;
+
+/// Name reported for matches found by the entropy check rather than a regex rule.
+const HIGH_ENTROPY_RULE: &str = "high_entropy";
+
+/// Minimum length of a token considered by the entropy check.
+const HIGH_ENTROPY_MIN_LEN: usize = 32;
+
+const DEFAULT_ENTROPY_THRESHOLD: f64 = 4.5;
+
+/// Fields of a share item, and of its `data`, that identify items rather than hold content.
+/// They are never masked: sync merges by `_key` and the share page groups parts by these IDs.
+const IDENTIFIER_FIELDS: &[&str] = &[
+    "_key", "_type", "type", "id", "sessionID", "messageID", "parentID", "callID",
+];
+
+/// Built-in rules as `(name, pattern)` pairs.
+/// If a pattern has a capture group named `secret`, only that group is masked.
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    ("aws_access_key_id", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    (
+        "aws_secret_access_key",
+        r#"(?i)aws_secret_access_key["']?\s*[:=]\s*["']?(?P<secret>[A-Za-z0-9/+=]{40})"#,
+    ),
+    (
+        "github_token",
+        r"\b(?:gh[pousr]_[A-Za-z0-9]{36,255}|github_pat_[A-Za-z0-9_]{22,255})\b",
+    ),
+    ("api_key", r"\bsk-[A-Za-z0-9_\-]{20,}"),
+    ("slack_token", r"\bxox[abposr]-[A-Za-z0-9\-]{10,}"),
+    (
+        "private_key",
+        r"-----BEGIN [A-Z0-9 ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z0-9 ]*PRIVATE KEY-----",
+    ),
+    (
+        "env_secret",
+        r#"(?im)^\s*(?:export\s+)?[A-Z0-9_]*(?:SECRET|TOKEN|PASSWORD|PASSWD|API_KEY|APIKEY|PRIVATE_KEY)[A-Z0-9_]*\s*=\s*["']?(?P<secret>[^\s"']+)"#,
+    ),
+];
+
+/// A named regex rule whose matches are masked before storage.
+#[derive(Debug, Clone)]
+pub struct RedactionRule {
+    pub name: String,
+    regex: Regex,
+}
+
+impl RedactionRule {
+    pub fn new(name: &str, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("Invalid redaction pattern for rule {}", name))?;
+        Ok(Self {
+            name: name.to_string(),
+            regex,
+        })
+    }
+}
+
+/// Custom rule as configured in settings or a rules file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleConfig {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// Redaction settings, configured in the `[redaction]` section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Set to `false` to store data unmodified
+    pub enabled: bool,
+    /// Additional rules on top of the built-in set
+    pub rules: Vec<RuleConfig>,
+    /// JSON file with additional `[{"name": ..., "pattern": ...}]` rules
+    pub rules_file: Option<PathBuf>,
+    /// Shannon entropy (bits/char) above which long tokens are masked; `0` disables the check
+    pub entropy_threshold: f64,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+            rules_file: None,
+            entropy_threshold: DEFAULT_ENTROPY_THRESHOLD,
+        }
+    }
+}
+
+/// Masks secrets in incoming share data.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<RedactionRule>,
+    entropy_threshold: Option<f64>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        let rules = BUILTIN_RULES
+            .iter()
+            .map(|(name, pattern)| {
+                RedactionRule::new(name, pattern).expect("built-in redaction rule must compile")
+            })
+            .collect();
+
+        Self {
+            rules,
+            entropy_threshold: Some(DEFAULT_ENTROPY_THRESHOLD),
+        }
+    }
+}
+
+impl Redactor {
+    /// A redactor that leaves all data untouched.
+    pub fn disabled() -> Self {
+        Self {
+            rules: Vec::new(),
+            entropy_threshold: None,
+        }
+    }
+
+    pub fn from_config(config: &RedactionConfig) -> Result<Self> {
+        if !config.enabled {
+            return Ok(Self::disabled());
+        }
+
+        let mut redactor = Self::default();
+
+        let mut rules = config.rules.clone();
+        if let Some(path) = &config.rules_file {
+            let content = fs::read_to_string(path).with_context(|| {
+                format!("Failed to read redaction rules file {}", path.display())
+            })?;
+            let file_rules: Vec<RuleConfig> = serde_json::from_str(&content).with_context(|| {
+                format!("Failed to parse redaction rules file {}", path.display())
+            })?;
+            rules.extend(file_rules);
+        }
+
+        for rule in rules {
+            redactor
+                .rules
+                .push(RedactionRule::new(&rule.name, &rule.pattern)?);
+        }
+
+        redactor.entropy_threshold =
+            (config.entropy_threshold > 0.0).then_some(config.entropy_threshold);
+
+        Ok(redactor)
+    }
+
+    /// Add a rule on top of the current set.
+    pub fn with_rule(mut self, rule: RedactionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.rules.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    /// Mask secrets in every string within `value`, returning how many matches each rule masked.
+    ///
+    /// Identifier fields (`_key`, `id`, `sessionID`, ...) of the item and of its `data`
+    /// object are left alone.
+    pub fn redact(&self, value: &mut Value) -> RedactionCounts {
+        let mut counts = RedactionCounts::new();
+        match value {
+            Value::Object(item) => {
+                for (field, item_value) in item.iter_mut() {
+                    if IDENTIFIER_FIELDS.contains(&field.as_str()) {
+                        continue;
+                    }
+                    match item_value {
+                        Value::Object(data) if field == "data" => {
+                            for (field, data_value) in data.iter_mut() {
+                                if !IDENTIFIER_FIELDS.contains(&field.as_str()) {
+                                    self.redact_value(data_value, &mut counts);
+                                }
+                            }
+                        }
+                        _ => self.redact_value(item_value, &mut counts),
+                    }
+                }
+            }
+            _ => self.redact_value(value, &mut counts),
+        }
+        counts
+    }
+
+    fn redact_value(&self, value: &mut Value, counts: &mut RedactionCounts) {
+        match value {
+            Value::String(s) => {
+                if let Some(redacted) = self.redact_str(s, counts) {
+                    *s = redacted;
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.redact_value(item, counts);
+                }
+            }
+            Value::Object(map) => {
+                for (_, item) in map.iter_mut() {
+                    self.redact_value(item, counts);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the masked string, or `None` if nothing matched.
+    fn redact_str(&self, input: &str, counts: &mut RedactionCounts) -> Option<String> {
+        let mut text = input.to_string();
+        let mut changed = false;
+
+        for rule in &self.rules {
+            let mut found = 0;
+            let mask = format!("[REDACTED:{}]", rule.name);
+            let replaced = rule.regex.replace_all(&text, |caps: &regex::Captures| {
+                found += 1;
+                let whole = caps.get(0).expect("capture 0 always exists");
+                match caps.name("secret") {
+                    Some(secret) => {
+                        let start = secret.start() - whole.start();
+                        let end = secret.end() - whole.start();
+                        let matched = whole.as_str();
+                        format!("{}{}{}", &matched[..start], mask, &matched[end..])
+                    }
+                    None => mask.clone(),
+                }
+            });
+
+            if found > 0 {
+                text = replaced.into_owned();
+                *counts.entry(rule.name.clone()).or_insert(0) += found;
+                changed = true;
+            }
+        }
+
+        if let Some(threshold) = self.entropy_threshold {
+            let mut found = 0;
+            let replaced = high_entropy_regex().replace_all(&text, |caps: &regex::Captures| {
+                let token = &caps[0];
+                if is_high_entropy(token, threshold) {
+                    found += 1;
+                    format!("[REDACTED:{}]", HIGH_ENTROPY_RULE)
+                } else {
+                    token.to_string()
+                }
+            });
+
+            if found > 0 {
+                text = replaced.into_owned();
+                *counts.entry(HIGH_ENTROPY_RULE.to_string()).or_insert(0) += found;
+                changed = true;
+            }
+        }
+
+        changed.then_some(text)
+    }
+}
+
+fn high_entropy_regex() -> &'static Regex {
+    static REGEX: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(&format!(
+            r"[A-Za-z0-9+/_\-]{{{},}}={{0,2}}",
+            HIGH_ENTROPY_MIN_LEN
+        ))
+        .expect("high entropy regex must compile")
+    })
+}
+
+/// Long tokens mixing letters and digits with high Shannon entropy look like generated secrets.
+fn is_high_entropy(token: &str, threshold: f64) -> bool {
+    let has_digit = token.chars().any(|c| c.is_ascii_digit());
+    let has_alpha = token.chars().any(|c| c.is_ascii_alphabetic());
+    has_digit && has_alpha && shannon_entropy(token) >= threshold
+}
+
+fn shannon_entropy(s: &str) -> f64 {
+    let mut freq: BTreeMap<char, usize> = BTreeMap::new();
+    for c in s.chars() {
+        *freq.entry(c).or_insert(0) += 1;
+    }
+
+    let len = s.chars().count() as f64;
+    freq.values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
@@ -0,0 +1,517 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::fmt::Write;
+
+use crate::core::transcript::{created_at, str_field, ToolCall, Transcript};
+
+/// Reasoning longer than this many lines starts collapsed.
+const REASONING_COLLAPSE_LINES: usize = 5;
+
+/// Tool output longer than this many lines starts collapsed.
+pub(crate) const OUTPUT_COLLAPSE_LINES: usize = 10;
+
+/// Lines of collapsed tool output shown as a preview.
+const OUTPUT_PREVIEW_LINES: usize = 5;
+
+const MISSING_DATA_HTML: &str = r#"<div class="error-content"><h1>Share Data Missing</h1><p>The share data is incomplete or corrupted.</p><a href="/">Go Home</a></div>"#;
+
+/// Render a share's conversation to the HTML that `static/share.js` used to build in
+/// the browser, so the page is readable without JavaScript.
+pub fn render_transcript(transcript: &Transcript) -> String {
+    Renderer::default().transcript(transcript)
+}
+
+/// Which part of a share an embed shows.
+#[derive(Debug, Clone, Default)]
+pub struct EmbedOptions {
+    /// Link to the full share page
+    pub share_url: String,
+    /// First message shown, 1-based
+    pub from: Option<usize>,
+    /// Last message shown, inclusive
+    pub to: Option<usize>,
+}
+
+/// Render the compact conversation view used by `/share/:id/embed`: a title bar linking to
+/// the full share and the selected messages, without session stats or diffs.
+pub fn render_embed(transcript: &Transcript, options: &EmbedOptions) -> String {
+    Renderer::default().embed(transcript, options)
+}
+
+/// Escape text for use in HTML content and quoted attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Builds the page markup. Element ids are numbered in document order so the output
+/// is deterministic.
+#[derive(Default)]
+struct Renderer {
+    next_id: usize,
+}
+
+impl Renderer {
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", prefix, self.next_id)
+    }
+
+    fn transcript(&mut self, transcript: &Transcript) -> String {
+        let Some(session) = &transcript.session else {
+            return MISSING_DATA_HTML.to_string();
+        };
+
+        let messages = transcript.messages();
+        let mut html = String::new();
+
+        html.push_str(r#"<div class="share-container">"#);
+        html.push_str(concat!(
+            r#"<header class="header">"#,
+            r#"<div class="header-left"><h1>Opencode Share</h1></div>"#,
+            r#"<div class="header-actions">"#,
+            r#"<a href="https://github.com/sst/opencode" target="_blank" rel="noopener">GitHub</a>"#,
+            r#"<a href="https://opencode.ai/discord" target="_blank" rel="noopener">Discord</a>"#,
+            r#"</div></header>"#,
+        ));
+
+        html.push_str(r#"<div class="content"><div class="session-info">"#);
+        let _ = write!(
+            html,
+            r#"<div class="session-title">{}</div><div class="session-meta"><span>v{}</span>"#,
+            escape_html(str_field(session, "title")),
+            escape_html(session.get("version").and_then(Value::as_str).unwrap_or("1.0.0")),
+        );
+        if let Some(created) = created_at(session) {
+            let _ = write!(html, "<span>•</span><span>{}</span>", time_element(created, "%Y-%m-%d"));
+        }
+        if let Some(directory) = session.get("directory").and_then(Value::as_str) {
+            let _ = write!(html, "<span>•</span><span>{}</span>", escape_html(directory));
+        }
+        if let Some(files) = session.pointer("/summary/files").filter(|files| truthy(files)) {
+            let _ = write!(html, "<span>•</span><span>{} files</span>", number(files));
+        }
+        html.push_str("</div></div>");
+
+        let _ = write!(
+            html,
+            concat!(
+                r#"<div class="session-stats">"#,
+                r#"<div class="stat-item"><span class="stat-label">Messages:</span><span class="stat-value">{}</span></div>"#,
+                r#"<div class="stat-item"><span class="stat-label">Session:</span><span class="stat-value">{}</span></div>"#,
+                "</div>",
+            ),
+            messages.len(),
+            escape_html(str_field(session, "id")),
+        );
+
+        html.push_str(r#"<div class="session-messages">"#);
+        for message in messages {
+            self.message(&mut html, transcript, message);
+        }
+        html.push_str("</div>");
+
+        if !transcript.diffs.is_empty() {
+            let _ = write!(
+                html,
+                r#"<div class="diff-container"><div class="diff-header">{} Files Changed</div>"#,
+                transcript.diffs.len()
+            );
+            for diff in &transcript.diffs {
+                self.diff(&mut html, diff);
+            }
+            html.push_str("</div>");
+        }
+
+        html.push_str("</div></div>");
+        html
+    }
+
+    fn embed(&mut self, transcript: &Transcript, options: &EmbedOptions) -> String {
+        let Some(session) = &transcript.session else {
+            return MISSING_DATA_HTML.to_string();
+        };
+
+        let messages = transcript.messages();
+        let from = options.from.unwrap_or(1).max(1);
+        let to = options.to.unwrap_or(messages.len()).min(messages.len());
+        let share_url = escape_html(&options.share_url);
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            concat!(
+                r#"<div class="share-container embed">"#,
+                r#"<header class="header"><div class="header-left"><a class="embed-title" href="{url}" target="_blank" rel="noopener">{title}</a></div>"#,
+                r#"<div class="header-actions"><a href="{url}" target="_blank" rel="noopener">Open share</a></div></header>"#,
+                r#"<div class="content"><div class="session-messages">"#,
+            ),
+            url = share_url,
+            title = escape_html(str_field(session, "title")),
+        );
+        if from <= to {
+            for message in &messages[from - 1..to] {
+                self.message(&mut html, transcript, message);
+            }
+        }
+        html.push_str("</div></div></div>");
+        html
+    }
+
+    fn message(&mut self, html: &mut String, transcript: &Transcript, message: &Value) {
+        let role = message.get("role").and_then(Value::as_str).unwrap_or("unknown");
+        let (content, rich) = self.message_content(transcript, message, role);
+
+        html.push_str(r#"<div class="message"><div class="message-header">"#);
+        let _ = write!(
+            html,
+            r#"<div class="message-role">{}</div><div class="message-meta">"#,
+            if role == "user" { "👤 User" } else { "🤖 Assistant" }
+        );
+        if let Some(model) = message.get("modelID").and_then(Value::as_str) {
+            let _ = write!(html, r#"<span class="message-model">{}</span>"#, escape_html(model));
+        }
+        let _ = write!(
+            html,
+            r#"<span class="message-time">{}</span>"#,
+            created_at(message)
+                .map(|created| time_element(created, "%Y-%m-%d %H:%M:%S UTC"))
+                .unwrap_or_default()
+        );
+        let input = message.pointer("/tokens/input").filter(|tokens| truthy(tokens));
+        let output = message.pointer("/tokens/output").filter(|tokens| truthy(tokens));
+        if input.is_some() || output.is_some() {
+            html.push_str(r#"<span class="message-tokens">"#);
+            if let Some(input) = input {
+                let _ = write!(html, "📥 {}", number(input));
+            }
+            if let Some(output) = output {
+                let _ = write!(html, " 📤 {}", number(output));
+            }
+            html.push_str("</span>");
+        }
+        let _ = write!(
+            html,
+            r#"</div></div><div class="message-content{}">{}</div></div>"#,
+            if rich { " has-rich-content" } else { "" },
+            content
+        );
+    }
+
+    /// Returns the escaped message body and whether it contains rich markup.
+    fn message_content(
+        &mut self,
+        transcript: &Transcript,
+        message: &Value,
+        role: &str,
+    ) -> (String, bool) {
+        if let Some(content) = message.get("content").and_then(Value::as_str) {
+            if !content.is_empty() {
+                return (escape_html(content), false);
+            }
+        }
+
+        let parts = transcript.parts(str_field(message, "id"));
+        if parts.is_empty() {
+            let summary = format!(
+                "[{} message via {} - {}]",
+                role,
+                message.get("modelID").and_then(Value::as_str).unwrap_or("unknown model"),
+                message.get("finish").and_then(Value::as_str).unwrap_or("unknown"),
+            );
+            return (escape_html(&summary), false);
+        }
+
+        let mut content = String::new();
+        let mut rich = false;
+
+        for part in parts {
+            match str_field(part, "type") {
+                "text" => {
+                    let text = str_field(part, "text");
+                    if !text.is_empty() {
+                        content.push_str(&escape_html(text));
+                        content.push_str("\n\n");
+                    }
+                }
+                "reasoning" => {
+                    let text = str_field(part, "text").trim();
+                    if !text.is_empty() {
+                        self.reasoning(&mut content, text);
+                        rich = true;
+                    }
+                }
+                "tool" | "tool-call" => {
+                    if let Some(state) = part.get("state") {
+                        self.tool_call(&mut content, state, part.get("tool").and_then(Value::as_str));
+                        rich = true;
+                    }
+                }
+                "step-start" => {
+                    content.push_str(r#"<div class="step-marker step-start">🚀 Step Started</div>"#);
+                    content.push_str("\n\n");
+                    rich = true;
+                }
+                "step-finish" => {
+                    let tokens = part.get("tokens").map(token_usage).unwrap_or_default();
+                    let _ = write!(
+                        content,
+                        r#"<div class="step-marker step-complete">✅ Step Completed{}</div>"#,
+                        if tokens.is_empty() { String::new() } else { format!(" {}", tokens) }
+                    );
+                    content.push_str("\n\n");
+                    rich = true;
+                }
+                other => {
+                    let text = str_field(part, "text");
+                    if !text.is_empty() {
+                        let _ = write!(
+                            content,
+                            "📝 **{}:**\n{}\n\n",
+                            escape_html(other),
+                            escape_html(text)
+                        );
+                    }
+                }
+            }
+        }
+
+        let content = content.trim();
+        if content.is_empty() {
+            (escape_html(&format!("[{} message - no content]", role)), false)
+        } else {
+            (content.to_string(), rich)
+        }
+    }
+
+    fn reasoning(&mut self, html: &mut String, text: &str) {
+        let id = self.id("reasoning");
+        let collapsible = text.lines().count() > REASONING_COLLAPSE_LINES;
+
+        let _ = write!(
+            html,
+            r#"<div class="reasoning-block" id="{}"><div class="reasoning-header"><span class="reasoning-icon">🤔</span><span class="reasoning-title">Thinking Process</span>"#,
+            id
+        );
+        if collapsible {
+            let _ = write!(
+                html,
+                r#"<button class="reasoning-toggle" data-reasoning-id="{}"><span class="show-text">Show details</span><span class="hide-text">Hide</span></button>"#,
+                id
+            );
+        }
+        let _ = write!(
+            html,
+            r#"</div><div class="reasoning-content{}"><pre><code>{}</code></pre></div></div>"#,
+            if collapsible { " collapsible" } else { "" },
+            escape_html(text)
+        );
+    }
+
+    fn tool_call(&mut self, html: &mut String, state: &Value, tool: Option<&str>) {
+        let call = ToolCall::from_state(state, tool);
+        let status = escape_html(call.status);
+
+        let _ = write!(
+            html,
+            r#"<div class="tool-call" data-status="{status}"><div class="tool-header"><span class="tool-icon">🔧</span><span class="tool-title">{}{}</span><span class="tool-status status-{status}">{status}</span></div><div class="tool-details">"#,
+            escape_html(call.tool),
+            if call.title.is_empty() { String::new() } else { format!(" - {}", escape_html(call.title)) },
+        );
+
+        if let Some(input) = state.get("input").filter(|input| truthy(input)) {
+            html.push_str(r#"<div class="tool-input"><div class="tool-label">Input</div>"#);
+            if let Some(input) = input.as_str() {
+                let _ = write!(html, r#"<code class="tool-inline-code">{}</code>"#, escape_html(input));
+            } else if let Some(command) = input.get("command").and_then(Value::as_str) {
+                let _ = write!(
+                    html,
+                    r#"<div class="tool-command"><span class="prompt">$</span><code>{}</code></div>"#,
+                    escape_html(command)
+                );
+                if let Some(description) = input.get("description").and_then(Value::as_str) {
+                    let _ = write!(html, r#"<div class="tool-description">{}</div>"#, escape_html(description));
+                }
+            } else {
+                let json = serde_json::to_string_pretty(input).unwrap_or_default();
+                let _ = write!(html, r#"<pre class="tool-json"><code>{}</code></pre>"#, escape_html(&json));
+            }
+            html.push_str("</div>");
+        }
+
+        let output = str_field(state, "output").trim();
+        if !output.is_empty() {
+            let id = self.id("tool");
+            let lines = output.lines().count();
+
+            if lines > OUTPUT_COLLAPSE_LINES {
+                let preview = output.lines().take(OUTPUT_PREVIEW_LINES).collect::<Vec<_>>().join("\n");
+                let _ = write!(
+                    html,
+                    concat!(
+                        r#"<div class="tool-output tool-output-collapsible" id="{id}"><div class="tool-label">Output"#,
+                        r#"<button class="tool-expand-btn" data-tool-id="{id}"><span class="expand-text">Show full output ({lines} lines)</span><span class="collapse-text">Show less</span></button></div>"#,
+                        r#"<div class="output-preview"><pre><code>{preview}</code></pre></div>"#,
+                        r#"<div class="output-full"><pre><code>{full}</code></pre></div></div>"#,
+                    ),
+                    id = id,
+                    lines = lines,
+                    preview = escape_html(&preview),
+                    full = escape_html(output),
+                );
+            } else {
+                let _ = write!(
+                    html,
+                    r#"<div class="tool-output" id="{}"><div class="tool-label">Output</div><pre><code>{}</code></pre></div>"#,
+                    id,
+                    escape_html(output)
+                );
+            }
+        }
+
+        let mut metadata = Vec::new();
+        if let Some(exit) = state.pointer("/metadata/exit").filter(|exit| !exit.is_null()) {
+            let class = if exit.as_i64() == Some(0) { "exit-success" } else { "exit-error" };
+            metadata.push(format!(r#"<span class="{}">Exit: {}</span>"#, class, number(exit)));
+        }
+        if let Some(duration) = call.duration {
+            metadata.push(format!(r#"<span class="tool-duration">⏱ {}ms</span>"#, duration));
+        }
+        if !metadata.is_empty() {
+            let _ = write!(html, r#"<div class="tool-metadata">{}</div>"#, metadata.join(" • "));
+        }
+
+        html.push_str("</div></div>");
+    }
+
+    fn diff(&mut self, html: &mut String, diff: &Value) {
+        let id = self.id("diff");
+        let before = str_field(diff, "before");
+        let after = str_field(diff, "after");
+
+        let mut before_html = String::new();
+        let mut after_html = String::new();
+        let (mut before_line, mut after_line) = (1, 1);
+        let (mut added, mut removed) = (0, 0);
+
+        for change in TextDiff::from_lines(before, after).iter_all_changes() {
+            let line = escape_html(change.value().trim_end_matches(['\n', '\r']));
+            match change.tag() {
+                ChangeTag::Equal => {
+                    diff_row(&mut before_html, "unchanged", Some(before_line), &line);
+                    diff_row(&mut after_html, "unchanged", Some(after_line), &line);
+                    before_line += 1;
+                    after_line += 1;
+                }
+                ChangeTag::Delete => {
+                    diff_row(&mut before_html, "removed", Some(before_line), &line);
+                    diff_row(&mut after_html, "empty", None, "&nbsp;");
+                    before_line += 1;
+                    removed += 1;
+                }
+                ChangeTag::Insert => {
+                    diff_row(&mut before_html, "empty", None, "&nbsp;");
+                    diff_row(&mut after_html, "added", Some(after_line), &line);
+                    after_line += 1;
+                    added += 1;
+                }
+            }
+        }
+
+        let _ = write!(
+            html,
+            concat!(
+                r#"<div class="diff-file" data-diff-id="{id}"><div class="diff-file-name">{file} <span class="diff-stats">+{added} -{removed}</span></div>"#,
+                r#"<div class="diff-content"><div class="diff-split-view">"#,
+                r#"<div class="diff-side diff-before"><div class="diff-side-header">Before</div><div class="diff-side-content diff-scrollable" id="{id}-before">{before}</div></div>"#,
+                r#"<div class="diff-divider"></div>"#,
+                r#"<div class="diff-side diff-after"><div class="diff-side-header">After</div><div class="diff-side-content diff-scrollable" id="{id}-after">{after}</div></div>"#,
+                "</div></div></div>",
+            ),
+            id = id,
+            file = escape_html(str_field(diff, "file")),
+            added = added,
+            removed = removed,
+            before = before_html,
+            after = after_html,
+        );
+    }
+}
+
+fn diff_row(html: &mut String, class: &str, line_number: Option<usize>, cell: &str) {
+    let _ = write!(
+        html,
+        r#"<div class="diff-row {}"><div class="diff-line-num">{}</div><div class="diff-cell">{}</div></div>"#,
+        class,
+        line_number.map(|n| n.to_string()).unwrap_or_default(),
+        if cell.is_empty() { "&nbsp;" } else { cell }
+    );
+}
+
+/// Token usage summary shown on step markers, e.g. `(📥 Input: 10, 📤 Output: 20)`.
+pub(crate) fn token_usage(tokens: &Value) -> String {
+    let mut parts = Vec::new();
+
+    for (pointer, label) in [
+        ("/input", "📥 Input"),
+        ("/output", "📤 Output"),
+        ("/reasoning", "🧠 Reasoning"),
+        ("/cache/read", "💾 Cache Read"),
+        ("/cache/write", "💾 Cache Write"),
+    ] {
+        if let Some(count) = tokens.pointer(pointer).filter(|count| truthy(count)) {
+            parts.push(format!("{}: {}", label, number(count)));
+        }
+    }
+    if let Some(cost) = tokens.get("cost").and_then(Value::as_f64).filter(|cost| *cost != 0.0) {
+        parts.push(format!("💰 Cost: ${:.6}", cost));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("({})", parts.join(", "))
+    }
+}
+
+/// A `<time>` element the page script localizes; the text is the UTC fallback.
+fn time_element(millis: i64, format: &str) -> String {
+    match DateTime::<Utc>::from_timestamp_millis(millis) {
+        Some(time) => format!(
+            r#"<time datetime="{}">{}</time>"#,
+            time.to_rfc3339(),
+            time.format(format)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Display a JSON scalar the way JavaScript would interpolate it.
+fn number(value: &Value) -> String {
+    match value {
+        Value::String(s) => escape_html(s),
+        other => escape_html(&other.to_string()),
+    }
+}
+
+/// JavaScript truthiness for JSON values.
+pub(crate) fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
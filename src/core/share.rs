@@ -1,36 +1,81 @@
-use crate::models::Share;
+use crate::core::import::SessionExport;
+use crate::core::limits::{LimitError, Limits};
+use crate::core::redact::Redactor;
+use crate::database::query_span;
+use crate::models::{Share, ShareListQuery, ShareSort, ShareSummary, SortOrder, StorageTotals};
 use anyhow::{anyhow, Result};
-use chrono::Utc;
-use serde_json::{json, Value};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
 use sqlx::PgPool;
+use std::sync::{Arc, OnceLock};
+use tracing::{error, instrument, Instrument};
 
 /// Default key used when client doesn't provide a `_key` field.
 /// Uses a UUID to ensure each item is treated as unique (append-only).
 const DEFAULT_KEY_PREFIX: &str = "auto";
 
+/// Returned (wrapped in `anyhow::Error`) when a share already exists for a session.
+#[derive(Debug, thiserror::Error)]
+#[error("Share already exists: {0}")]
+pub struct ShareExists(pub String);
+
+/// Returned (wrapped in `anyhow::Error`) when no share has the requested ID.
+#[derive(Debug, thiserror::Error)]
+#[error("Share not found: {0}")]
+pub struct ShareNotFound(pub String);
+
+/// Returned (wrapped in `anyhow::Error`) when the given secret does not match the share's.
+#[derive(Debug, thiserror::Error)]
+#[error("Share secret invalid: {0}")]
+pub struct InvalidSecret(pub String);
+
 pub struct ShareService {
     pool: PgPool,
+    limits: Limits,
+    redactor: Arc<Redactor>,
 }
 
 impl ShareService {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        static DEFAULT_REDACTOR: OnceLock<Arc<Redactor>> = OnceLock::new();
+
+        Self {
+            pool,
+            limits: Limits::default(),
+            redactor: DEFAULT_REDACTOR
+                .get_or_init(|| Arc::new(Redactor::default()))
+                .clone(),
+        }
     }
 
+    /// Override the default size limits enforced by `sync`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Override the default redaction rules applied by `sync`.
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    #[instrument(name = "share.create", skip_all, fields(share_id = %session_id))]
     pub async fn create(&self, session_id: String) -> Result<Share> {
         let id = session_id.clone();
         let secret = uuid::Uuid::new_v4().to_string();
 
         // Check if share already exists
         let existing = sqlx::query_as::<_, Share>(
-            "SELECT id, secret, session_id, data, created_at, updated_at FROM shares WHERE id = $1"
+            "SELECT id, secret, session_id, data, storage_bytes, redactions, created_at, updated_at FROM shares WHERE id = $1"
         )
         .bind(&id)
         .fetch_optional(&self.pool)
+        .instrument(query_span("SELECT", "shares"))
         .await?;
 
         if existing.is_some() {
-            return Err(anyhow!("Share already exists: {}", id));
+            return Err(ShareExists(id).into());
         }
 
         // Create new share with empty data array
@@ -38,7 +83,7 @@ impl ShareService {
             r#"
             INSERT INTO shares (id, secret, session_id, data, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, secret, session_id, data, created_at, updated_at
+            RETURNING id, secret, session_id, data, storage_bytes, redactions, created_at, updated_at
             "#
         )
         .bind(&id)
@@ -48,47 +93,246 @@ impl ShareService {
         .bind(Utc::now())
         .bind(Utc::now())
         .fetch_one(&self.pool)
-        .await?;
+        .instrument(query_span("INSERT", "shares"))
+        .await
+        .map_err(|e| match e {
+            // Created concurrently since the check above
+            sqlx::Error::Database(db) if db.is_unique_violation() => ShareExists(id.clone()).into(),
+            e => anyhow::Error::from(e),
+        })?;
 
         Ok(share)
     }
 
+    /// Create a share from an `opencode export` of a finished session.
+    ///
+    /// The items go through `sync` in batches of `max_sync_items`, so the usual limits and
+    /// redaction apply; if any batch is rejected the new share is removed again.
+    #[instrument(
+        name = "share.import",
+        skip_all,
+        fields(share_id = %export.session_id().unwrap_or_default(), messages = export.messages.len())
+    )]
+    pub async fn import(&self, export: SessionExport) -> Result<Share> {
+        let session_id = export
+            .session_id()
+            .ok_or_else(|| anyhow!("Session export has no session ID"))?
+            .to_string();
+        let items = export.into_items();
+
+        let share = self.create(session_id).await?;
+        for batch in items.chunks(self.limits.max_sync_items.max(1)) {
+            if let Err(e) = self.sync(&share.id, &share.secret, batch.to_vec()).await {
+                // Report the rejected batch, not a failed cleanup
+                if let Err(cleanup) = self.remove(&share.id, &share.secret).await {
+                    error!(
+                        "❌ Failed to remove rejected import - ID: {} - Error: {}",
+                        share.id, cleanup
+                    );
+                }
+                return Err(e);
+            }
+        }
+
+        self.get(&share.id)
+            .await?
+            .ok_or_else(|| ShareNotFound(share.id.clone()).into())
+    }
+
+    #[instrument(name = "share.get", skip_all, fields(share_id = %id))]
     pub async fn get(&self, id: &str) -> Result<Option<Share>> {
         let share = sqlx::query_as::<_, Share>(
-            "SELECT id, secret, session_id, data, created_at, updated_at FROM shares WHERE id = $1"
+            "SELECT id, secret, session_id, data, storage_bytes, redactions, created_at, updated_at FROM shares WHERE id = $1"
         )
         .bind(id)
         .fetch_optional(&self.pool)
+        .instrument(query_span("SELECT", "shares"))
         .await?;
 
         Ok(share)
     }
 
+    #[instrument(name = "share.remove", skip_all, fields(share_id = %id))]
     pub async fn remove(&self, id: &str, secret: &str) -> Result<()> {
         let share = self.get(id).await?;
-        let share = share.ok_or_else(|| anyhow!("Share not found: {}", id))?;
+        let share = share.ok_or_else(|| ShareNotFound(id.to_string()))?;
         
         if share.secret != secret {
-            return Err(anyhow!("Share secret invalid: {}", id));
+            return Err(InvalidSecret(id.to_string()).into());
         }
 
         // Delete share (single table operation)
         sqlx::query("DELETE FROM shares WHERE id = $1")
             .bind(id)
             .execute(&self.pool)
+            .instrument(query_span("DELETE", "shares"))
             .await?;
 
         Ok(())
     }
 
+    /// A page of shares without their data, ordered as requested.
+    #[instrument(name = "share.list", skip_all)]
+    pub async fn list(&self, query: &ShareListQuery) -> Result<Vec<ShareSummary>> {
+        let column = match query.sort {
+            ShareSort::Created => "created_at",
+            ShareSort::Updated => "updated_at",
+            ShareSort::Size => "storage_bytes",
+        };
+        let order = match query.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+
+        let shares = sqlx::query_as::<_, ShareSummary>(&format!(
+            r#"
+            SELECT id, session_id,
+                   (SELECT item->'data'->>'title'
+                    FROM jsonb_array_elements(CASE WHEN jsonb_typeof(data) = 'array' THEN data ELSE '[]' END) item
+                    WHERE item->>'type' = 'session'
+                    LIMIT 1) AS title,
+                   storage_bytes, created_at, updated_at
+            FROM shares
+            WHERE $1::TEXT IS NULL OR session_id = $1
+            ORDER BY {column} {order}, id {order}
+            LIMIT $2 OFFSET $3
+            "#
+        ))
+        .bind(&query.session_id)
+        .bind(query.limit)
+        .bind(query.offset)
+        .fetch_all(&self.pool)
+        .instrument(query_span("SELECT", "shares"))
+        .await?;
+
+        Ok(shares)
+    }
+
+    /// Number of shares, or of shares of `session_id` if given.
+    #[instrument(name = "share.count", skip_all)]
+    pub async fn count(&self, session_id: Option<&str>) -> Result<i64> {
+        let count = sqlx::query_scalar("SELECT COUNT(*) FROM shares WHERE $1::TEXT IS NULL OR session_id = $1")
+            .bind(session_id)
+            .fetch_one(&self.pool)
+            .instrument(query_span("SELECT", "shares"))
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Totals over all stored shares and views.
+    #[instrument(name = "share.storage_totals", skip_all)]
+    pub async fn storage_totals(&self) -> Result<StorageTotals> {
+        let totals = sqlx::query_as::<_, StorageTotals>(
+            r#"
+            SELECT COUNT(*) AS shares,
+                   COALESCE(SUM(storage_bytes), 0)::BIGINT AS storage_bytes,
+                   COALESCE(MAX(storage_bytes), 0)::BIGINT AS largest_share_bytes,
+                   COUNT(*) FILTER (WHERE data IS NULL OR data = '[]'::jsonb) AS empty_shares,
+                   (SELECT COUNT(*) FROM share_views) AS views,
+                   (pg_total_relation_size('shares') + pg_total_relation_size('share_views'))::BIGINT AS database_bytes
+            FROM shares
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .instrument(query_span("SELECT", "shares"))
+        .await?;
+
+        Ok(totals)
+    }
+
+    /// Recompute `storage_bytes` for shares flagged by the 005 migration, measuring compact
+    /// JSON like `sync` does. Returns the number of shares updated.
+    #[instrument(name = "share.recount_storage_bytes", skip_all)]
+    pub async fn recount_storage_bytes(&self) -> Result<u64> {
+        let mut updated = 0;
+        let mut after = String::new();
+
+        loop {
+            let rows: Vec<(String, Option<Value>)> = sqlx::query_as(
+                "SELECT id, data FROM shares WHERE storage_bytes_stale AND id > $1 ORDER BY id LIMIT 100",
+            )
+            .bind(&after)
+            .fetch_all(&self.pool)
+            .instrument(query_span("SELECT", "shares"))
+            .await?;
+            let Some((last, _)) = rows.last() else {
+                break;
+            };
+            after = last.clone();
+
+            for (id, data) in rows {
+                let storage_bytes = serde_json::to_vec(&data.unwrap_or_else(|| json!([])))?.len();
+                // A sync since the read already stored the right size and cleared the flag
+                let result = sqlx::query(
+                    "UPDATE shares SET storage_bytes = $2, storage_bytes_stale = FALSE WHERE id = $1 AND storage_bytes_stale",
+                )
+                .bind(&id)
+                .bind(storage_bytes as i64)
+                .execute(&self.pool)
+                .instrument(query_span("UPDATE", "shares"))
+                .await?;
+                updated += result.rows_affected();
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Delete a share without checking its secret, for operators.
+    /// Returns whether the share existed.
+    #[instrument(name = "share.delete", skip_all, fields(share_id = %id))]
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM shares WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .instrument(query_span("DELETE", "shares"))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete shares that were created but never received any data and have not been
+    /// touched since `before`. Returns the number of shares deleted.
+    #[instrument(name = "share.delete_abandoned", skip_all)]
+    pub async fn delete_abandoned(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM shares
+            WHERE (data IS NULL OR data = '[]'::jsonb) AND updated_at < $1
+            "#,
+        )
+        .bind(before)
+        .execute(&self.pool)
+        .instrument(query_span("DELETE", "shares"))
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(
+        name = "share.sync",
+        skip_all,
+        fields(share_id = %share_id, items = incoming_data.len())
+    )]
     pub async fn sync(&self, share_id: &str, secret: &str, incoming_data: Vec<Value>) -> Result<()> {
-        let share = self.get(share_id).await?;
-        let share = share.ok_or_else(|| anyhow!("Share not found: {}", share_id))?;
+        // Lock the row so concurrent syncs merge and check the quota one after another
+        let mut tx = self.pool.begin().await?;
+        let share = sqlx::query_as::<_, Share>(
+            "SELECT id, secret, session_id, data, storage_bytes, redactions, created_at, updated_at FROM shares WHERE id = $1 FOR UPDATE"
+        )
+        .bind(share_id)
+        .fetch_optional(&mut *tx)
+        .instrument(query_span("SELECT", "shares"))
+        .await?;
+        let share = share.ok_or_else(|| ShareNotFound(share_id.to_string()))?;
         
         if share.secret != secret {
-            return Err(anyhow!("Share secret invalid: {}", share_id));
+            return Err(InvalidSecret(share_id.to_string()).into());
         }
 
+        self.check_incoming(&incoming_data)?;
+
         // Get current data
         let current_data_value = share.data.unwrap_or(json!([]));
         let mut current_data: Vec<Value> = if let Some(data_array) = current_data_value.as_array() {
@@ -97,35 +341,69 @@ impl ShareService {
             Vec::new()
         };
 
-        // Merge incoming data with current data
-        for item in incoming_data {
+        // Redaction report keyed by item key, kept in step with the stored items
+        let mut redactions = match share.redactions {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+
+        // Mask secrets, then merge incoming data with current data
+        for mut item in incoming_data {
             let key = self.get_data_key(&item);
+            let counts = self.redactor.redact(&mut item);
+            // Keyless items get a fresh random key every time, so a report under it could
+            // never be looked up or replaced
+            let keyed = item.get("_key").and_then(Value::as_str).is_some();
+            if keyed {
+                if counts.is_empty() {
+                    redactions.remove(&key);
+                } else {
+                    redactions.insert(key.clone(), serde_json::to_value(counts)?);
+                }
+            }
             self.merge_data(&mut current_data, item, &key);
         }
 
-        // Convert back to JSON and update database
+        // Convert back to JSON and enforce the per-share quota
         let updated_json = serde_json::to_value(&current_data)?;
+        let storage_bytes = serde_json::to_vec(&updated_json)?.len();
+
+        if storage_bytes > self.limits.max_share_bytes {
+            return Err(LimitError::QuotaExceeded {
+                size: storage_bytes,
+                max: self.limits.max_share_bytes,
+            }
+            .into());
+        }
 
         sqlx::query(
             r#"
             UPDATE shares 
             SET data = $2,
-                updated_at = $3
+                storage_bytes = $3,
+                storage_bytes_stale = FALSE,
+                redactions = $4,
+                updated_at = $5
             WHERE id = $1
             "#
         )
         .bind(share_id)
         .bind(updated_json)
+        .bind(storage_bytes as i64)
+        .bind(Value::Object(redactions))
         .bind(Utc::now())
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .instrument(query_span("UPDATE", "shares"))
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
+    #[instrument(name = "share.get_data", skip_all, fields(share_id = %share_id))]
     pub async fn get_data(&self, share_id: &str) -> Result<Vec<Value>> {
         let share = self.get(share_id).await?;
-        let share = share.ok_or_else(|| anyhow!("Share not found: {}", share_id))?;
+        let share = share.ok_or_else(|| ShareNotFound(share_id.to_string()))?;
 
         // Simply return the stored data
         if let Some(data_value) = share.data {
@@ -137,6 +415,43 @@ impl ShareService {
         Ok(vec![])
     }
 
+    /// Return what was redacted from the share's stored items, keyed by item key.
+    #[instrument(name = "share.get_redactions", skip_all, fields(share_id = %share_id))]
+    pub async fn get_redactions(&self, share_id: &str, secret: &str) -> Result<Value> {
+        let share = self.get(share_id).await?;
+        let share = share.ok_or_else(|| ShareNotFound(share_id.to_string()))?;
+
+        if share.secret != secret {
+            return Err(InvalidSecret(share_id.to_string()).into());
+        }
+
+        Ok(share.redactions)
+    }
+
+    /// Reject sync payloads with too many items or oversized individual items.
+    fn check_incoming(&self, incoming_data: &[Value]) -> Result<()> {
+        if incoming_data.len() > self.limits.max_sync_items {
+            return Err(LimitError::TooManyItems {
+                count: incoming_data.len(),
+                max: self.limits.max_sync_items,
+            }
+            .into());
+        }
+
+        for item in incoming_data {
+            let size = serde_json::to_vec(item)?.len();
+            if size > self.limits.max_item_bytes {
+                return Err(LimitError::ItemTooLarge {
+                    size,
+                    max: self.limits.max_item_bytes,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Extract the merge key from a data item.
     /// Uses `_key` field if provided by client, otherwise generates a unique key.
     fn get_data_key(&self, data: &Value) -> String {
@@ -0,0 +1,223 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Token and cost totals over a session's messages.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Usage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cost: f64,
+}
+
+impl Usage {
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens
+            .saturating_add(self.output_tokens)
+            .saturating_add(self.reasoning_tokens)
+    }
+}
+
+/// What the share page and exports show about a tool call, read from a tool part's `state`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolCall<'a> {
+    pub tool: &'a str,
+    /// First non-empty title-like field, or `""`
+    pub title: &'a str,
+    pub status: &'a str,
+    /// Milliseconds from start to end, when both are set and in order
+    pub duration: Option<i64>,
+}
+
+impl<'a> ToolCall<'a> {
+    /// `tool` is the part's tool name; without one it is looked up in `state`.
+    pub fn from_state(state: &'a Value, tool: Option<&'a str>) -> Self {
+        let tool = tool
+            .or_else(|| ["toolType", "tool", "type"].iter().find_map(|name| state.get(*name)?.as_str()))
+            .unwrap_or("unknown");
+        let title = ["/title", "/name", "/description", "/metadata/description", "/input/description"]
+            .iter()
+            .filter_map(|pointer| state.pointer(pointer)?.as_str())
+            .find(|title| !title.is_empty())
+            .unwrap_or("");
+        let status = state.get("status").and_then(Value::as_str).unwrap_or("unknown");
+
+        let start = state.pointer("/time/start").and_then(Value::as_i64).filter(|t| *t != 0);
+        let end = state.pointer("/time/end").and_then(Value::as_i64).filter(|t| *t != 0);
+        let duration = start
+            .zip(end)
+            .and_then(|(start, end)| end.checked_sub(start))
+            .filter(|d| *d >= 0);
+
+        Self {
+            tool,
+            title,
+            status,
+            duration,
+        }
+    }
+}
+
+/// A share's stored items grouped the way the share page presents them.
+///
+/// Items are `{ "type": ..., "data": ... }` objects synced by opencode: one `session`,
+/// its `message`s, the `part`s of each message, an optional `session_diff` and the
+/// `model`s used.
+#[derive(Debug, Default)]
+pub struct Transcript {
+    pub session: Option<Value>,
+    pub diffs: Vec<Value>,
+    pub models: Vec<Value>,
+    messages: HashMap<String, Vec<Value>>,
+    parts: HashMap<String, Vec<Value>>,
+}
+
+impl Transcript {
+    pub fn from_items(items: &[Value]) -> Self {
+        let mut transcript = Self::default();
+
+        for item in items {
+            let Some(data) = item.get("data") else {
+                continue;
+            };
+
+            match item.get("type").and_then(Value::as_str) {
+                Some("session") => transcript.session = Some(data.clone()),
+                Some("message") => transcript
+                    .messages
+                    .entry(str_field(data, "sessionID").to_string())
+                    .or_default()
+                    .push(data.clone()),
+                Some("part") => transcript
+                    .parts
+                    .entry(str_field(data, "messageID").to_string())
+                    .or_default()
+                    .push(data.clone()),
+                Some("session_diff") => {
+                    transcript.diffs = data.as_array().cloned().unwrap_or_default()
+                }
+                Some("model") => transcript.models.push(data.clone()),
+                _ => {}
+            }
+        }
+
+        for messages in transcript.messages.values_mut() {
+            messages.sort_by_key(|message| created_at(message).unwrap_or(0));
+        }
+
+        // Order parts by time when every part has one; otherwise keep sync order,
+        // which is the order opencode produced them in
+        for parts in transcript.parts.values_mut() {
+            if parts.iter().all(|part| part_time(part).is_some()) {
+                parts.sort_by_key(|part| part_time(part).unwrap_or(0));
+            }
+        }
+
+        transcript
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session.as_ref()?.get("id")?.as_str()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.session.as_ref()?.get("title")?.as_str()
+    }
+
+    /// Directory the session worked in: the project root reported on its messages, else the
+    /// session's `directory`. Changed file paths are made relative to it.
+    pub fn root(&self) -> Option<&str> {
+        self.messages()
+            .iter()
+            .find_map(|message| message.pointer("/path/root")?.as_str())
+            .or_else(|| self.session.as_ref()?.get("directory")?.as_str())
+            .filter(|root| !root.is_empty())
+    }
+
+    /// Messages of the shared session, oldest first.
+    pub fn messages(&self) -> &[Value] {
+        self.session_id()
+            .and_then(|id| self.messages.get(id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Parts of a message in display order.
+    pub fn parts(&self, message_id: &str) -> &[Value] {
+        self.parts
+            .get(message_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Text of the first user message, if any.
+    pub fn first_prompt(&self) -> Option<String> {
+        let message = self
+            .messages()
+            .iter()
+            .find(|message| str_field(message, "role") == "user")?;
+
+        if let Some(content) = message.get("content").and_then(Value::as_str) {
+            return Some(content.to_string());
+        }
+
+        let text = self
+            .parts(str_field(message, "id"))
+            .iter()
+            .filter(|part| str_field(part, "type") == "text")
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        (!text.trim().is_empty()).then_some(text)
+    }
+
+    /// Token and cost totals reported on the session's messages.
+    pub fn usage(&self) -> Usage {
+        let mut usage = Usage::default();
+        for message in self.messages() {
+            let count = |pointer: &str| message.pointer(pointer).and_then(Value::as_i64).unwrap_or(0);
+            usage.input_tokens = usage.input_tokens.saturating_add(count("/tokens/input"));
+            usage.output_tokens = usage.output_tokens.saturating_add(count("/tokens/output"));
+            usage.reasoning_tokens = usage.reasoning_tokens.saturating_add(count("/tokens/reasoning"));
+            usage.cost += message.get("cost").and_then(Value::as_f64).unwrap_or(0.0);
+        }
+        usage
+    }
+
+    /// Number of files changed: the stored diffs, else the session summary's count.
+    pub fn files_changed(&self) -> usize {
+        if !self.diffs.is_empty() {
+            return self.diffs.len();
+        }
+        self.session
+            .as_ref()
+            .and_then(|session| session.pointer("/summary/files"))
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize
+    }
+
+    /// Model of the first assistant message that names one.
+    pub fn model(&self) -> Option<&str> {
+        self.messages()
+            .iter()
+            .find_map(|message| message.get("modelID").and_then(Value::as_str))
+    }
+}
+
+/// String field of a JSON object, or `""` when missing.
+pub fn str_field<'a>(value: &'a Value, name: &str) -> &'a str {
+    value.get(name).and_then(Value::as_str).unwrap_or("")
+}
+
+/// Milliseconds since the epoch at which a message or session was created.
+pub fn created_at(value: &Value) -> Option<i64> {
+    value.get("time")?.get("created")?.as_i64()
+}
+
+fn part_time(part: &Value) -> Option<i64> {
+    let time = part.get("time")?;
+    time.get("start")
+        .and_then(Value::as_i64)
+        .or_else(|| time.get("end").and_then(Value::as_i64))
+}
@@ -1,13 +1,59 @@
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
+use std::collections::HashSet;
 use std::sync::Arc;
+use tracing::{info_span, Span};
+
+use crate::settings::DatabaseSettings;
 
 pub type DbPool = Arc<PgPool>;
 
-pub async fn create_pool(database_url: &str) -> anyhow::Result<PgPool> {
-    let pool = PgPool::connect(database_url).await?;
+/// Migrations embedded from `./migrations`.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+pub async fn create_pool(settings: &DatabaseSettings) -> anyhow::Result<PgPool> {
+    let pool = PgPoolOptions::new()
+        .max_connections(settings.max_connections)
+        .connect(&settings.url)
+        .await?;
     
     // PostgreSQL has foreign key constraints enabled by default
     // and doesn't need WAL mode like SQLite
     
     Ok(pool)
-}
\ No newline at end of file
+}
+
+/// Client span for a single database query, named e.g. `SELECT shares` in trace exports.
+pub fn query_span(operation: &'static str, table: &'static str) -> Span {
+    info_span!(
+        "db.query",
+        otel.name = %format!("{} {}", operation, table),
+        otel.kind = "client",
+        db.system = "postgresql",
+        db.operation = operation,
+        db.sql.table = table,
+    )
+}
+
+/// Check that a connection can be acquired and used.
+pub async fn ping(pool: &PgPool) -> anyhow::Result<()> {
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(())
+}
+
+/// Versions of embedded migrations that have not been applied successfully.
+pub async fn pending_migrations(pool: &PgPool) -> anyhow::Result<Vec<i64>> {
+    let applied: HashSet<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    Ok(MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
+}
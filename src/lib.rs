@@ -0,0 +1,88 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+pub mod cli;
+pub mod core;
+pub mod database;
+pub mod metrics;
+pub mod middleware;
+pub mod models;
+pub mod routes;
+pub mod server;
+pub mod settings;
+pub mod telemetry;
+pub mod templates;
+
+use core::analytics::ViewService;
+use core::og_image::OgImageRenderer;
+use core::public_url::PublicUrl;
+use core::redact::Redactor;
+use core::share::ShareService;
+use metrics::Metrics;
+use middleware::TrustedProxies;
+use settings::Settings;
+use templates::Templates;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: PgPool,
+    pub settings: Arc<Settings>,
+    pub redactor: Arc<Redactor>,
+    pub trusted_proxies: TrustedProxies,
+    pub public_url: PublicUrl,
+    pub metrics: Arc<Metrics>,
+    pub templates: Arc<Templates>,
+    pub og_images: Arc<OgImageRenderer>,
+    /// Salt for visitor IP hashes in `share_views`
+    pub ip_hash_salt: Arc<str>,
+}
+
+impl AppState {
+    /// Build the application state, compiling the parts of `settings` used per request.
+    pub fn new(db: PgPool, settings: Settings) -> Result<Self> {
+        let redactor = Redactor::from_config(&settings.redaction)?;
+        let trusted_proxies = TrustedProxies::from_list(&settings.server.trusted_proxies)?;
+        let public_url = PublicUrl::new(
+            settings.server.public_base_url.as_deref(),
+            settings.server.allowed_hosts.clone(),
+        )?;
+        let templates = Templates::new(&settings.paths)?;
+
+        let ip_hash_salt = match settings.analytics.ip_hash_salt.as_deref() {
+            Some(salt) if !salt.is_empty() => salt.to_string(),
+            _ => {
+                if settings.analytics.enabled {
+                    tracing::warn!(
+                        "analytics.ip_hash_salt is not set; unique visitor counts restart with the server"
+                    );
+                }
+                uuid::Uuid::new_v4().to_string()
+            }
+        };
+
+        Ok(Self {
+            db,
+            settings: Arc::new(settings),
+            redactor: Arc::new(redactor),
+            trusted_proxies,
+            public_url,
+            metrics: Arc::new(Metrics::new()?),
+            templates: Arc::new(templates),
+            og_images: Arc::new(OgImageRenderer::new()),
+            ip_hash_salt: ip_hash_salt.into(),
+        })
+    }
+
+    /// Build a `ShareService` configured from the application state.
+    pub fn share_service(&self) -> ShareService {
+        ShareService::new(self.db.clone())
+            .with_limits(self.settings.limits.clone())
+            .with_redactor(self.redactor.clone())
+    }
+
+    /// Build a `ViewService` for recording and reporting share views.
+    pub fn view_service(&self) -> ViewService {
+        ViewService::new(self.db.clone(), self.ip_hash_salt.clone())
+    }
+}
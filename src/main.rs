@@ -1,83 +1,165 @@
 use axum::{
+    extract::DefaultBodyLimit,
     middleware::from_fn_with_state,
     routing::get,
     Router,
 };
+use axum::http::{HeaderName, HeaderValue};
+use clap::Parser;
 use sqlx::PgPool;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tracing::info;
 
-mod core;
-mod database;
-mod middleware;
-mod models;
-mod routes;
-
-use routes::{api_routes, share_routes};
-use middleware::access_log_middleware;
-
-#[derive(Clone)]
-pub struct AppState {
-    pub db: PgPool,
-}
+use opencode_share::cli::{run_gc, run_share, Cli, Command};
+use opencode_share::core::share::ShareService;
+use opencode_share::database::{create_pool, pending_migrations, MIGRATOR};
+use opencode_share::middleware::{
+    access_log_middleware, admin_auth_middleware, metrics_middleware, REQUEST_ID_HEADER,
+};
+use opencode_share::routes::{admin_routes, api_routes, embed_routes, health_routes, share_routes};
+use opencode_share::server::{serve, shutdown_signal, Listener};
+use opencode_share::settings::{CorsSettings, Settings};
+use opencode_share::telemetry::Telemetry;
+use opencode_share::AppState;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // Load environment variables
     dotenv::dotenv().ok();
     
+    // Load configuration
+    let settings = Settings::load()?;
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => run_server(settings).await,
+        Command::Migrate => {
+            let pool = create_pool(&settings.database).await?;
+            migrate(&pool).await
+        }
+        Command::Share(command) => {
+            let pool = create_pool(&settings.database).await?;
+            let state = AppState::new(pool, settings)?;
+            run_share(&state, command, &mut std::io::stdout().lock()).await
+        }
+        Command::Gc(args) => {
+            let pool = create_pool(&settings.database).await?;
+            let state = AppState::new(pool, settings)?;
+            run_gc(&state, args, &mut std::io::stdout().lock()).await
+        }
+    }
+}
+
+/// Apply pending migrations and report which ones ran.
+async fn migrate(pool: &PgPool) -> anyhow::Result<()> {
+    // Before the first migration there is no migrations table to compare against
+    let pending = pending_migrations(pool)
+        .await
+        .unwrap_or_else(|_| MIGRATOR.iter().map(|migration| migration.version).collect());
+
+    MIGRATOR.run(pool).await?;
+    let recounted = ShareService::new(pool.clone()).recount_storage_bytes().await?;
+
+    if pending.is_empty() {
+        println!("Database is up to date");
+    } else {
+        println!("Applied migrations: {:?}", pending);
+    }
+    if recounted > 0 {
+        println!("Recounted storage size of {} shares", recounted);
+    }
+    Ok(())
+}
+
+async fn run_server(settings: Settings) -> anyhow::Result<()> {
     // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "opencode_share=debug,tower_http=debug".into()),
-        )
-        .init();
+    let telemetry = Telemetry::init(&settings.logging, &settings.telemetry)?;
 
     // Initialize database
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share".to_string());
-    
-    println!("Using database: {}", database_url);
-    let pool = PgPool::connect(&database_url).await?;
+    println!("Using database: {}", settings.database.url);
+    let pool = create_pool(&settings.database).await?;
     
     // Run migrations
-    sqlx::migrate!("./migrations")
+    MIGRATOR
         .run(&pool)
         .await
         .expect("Failed to run database migrations");
+
+    let recounted = ShareService::new(pool.clone()).recount_storage_bytes().await?;
+    if recounted > 0 {
+        info!("Recounted storage size of {} shares", recounted);
+    }
     
-    let app_state = AppState { db: pool };
+    info!("Using limits: {:?}", settings.limits);
+
+    let app_state = AppState::new(pool.clone(), settings.clone())?;
+    info!("Using redaction rules: {:?}", app_state.redactor.rule_names());
+    info!("Using trusted proxies: {:?}", app_state.trusted_proxies);
+    info!("Using public URL config: {:?}", app_state.public_url);
 
     // Build the application
     let app = Router::new()
-        // Apply access log middleware to all routes
-        .layer(from_fn_with_state(app_state.clone(), access_log_middleware))
         // API routes
-        .nest("/api", api_routes())
+        .nest(
+            "/api",
+            api_routes().layer(DefaultBodyLimit::max(settings.limits.max_body_bytes)),
+        )
+        // Operator API, disabled unless ADMIN_TOKEN is set
+        .nest(
+            "/admin/api",
+            admin_routes().layer(from_fn_with_state(app_state.clone(), admin_auth_middleware)),
+        )
         // Share pages
         .nest("/share", share_routes())
+        // oEmbed provider for share links
+        .merge(embed_routes())
         // Static files
-        .nest_service("/static", tower_http::services::ServeDir::new("static"))
+        .nest_service("/static", tower_http::services::ServeDir::new(&settings.paths.static_dir))
         // Root route
         .route("/", get(index))
+        // Health, readiness and version probes
+        .merge(health_routes())
+        // Apply access log middleware to all routes
+        .layer(from_fn_with_state(app_state.clone(), access_log_middleware))
+        // Request metrics per route template
+        .layer(from_fn_with_state(app_state.clone(), metrics_middleware))
         // CORS
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .layer(cors_layer(&settings.cors)?)
         .with_state(app_state);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3006").await?;
-    info!("Server listening on {}", listener.local_addr()?);
-    
-    axum::serve(listener, app).await?;
+    let listener = Listener::bind(&settings.server).await?;
+    info!("Server listening on {}", listener.describe());
     
+    serve(listener, app, shutdown_signal(), settings.server.drain_timeout()).await?;
+
+    info!("Closing database connections");
+    pool.close().await;
+    telemetry.shutdown();
+    info!("Shutdown complete");
+
     Ok(())
 }
 
+fn cors_layer(cors: &CorsSettings) -> anyhow::Result<CorsLayer> {
+    let origins = &cors.allowed_origins;
+    let allow_origin = if origins.is_empty() || origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|o| HeaderValue::from_str(o))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)]))
+}
+
 async fn index() -> &'static str {
     info!("🏠 Home page requested");
     "Hello World"
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Prefix of every metric name.
+const NAMESPACE: &str = "opencode_share";
+
+/// Content type of the Prometheus text exposition format.
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Route label for requests that did not match any route, keeping label cardinality bounded.
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const SIZE_BUCKETS: &[f64] = &[
+    256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 16777216.0,
+];
+
+const ITEM_BUCKETS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0];
+
+/// Prometheus metrics for the service, rendered by `GET /metrics`.
+///
+/// Request metrics are recorded by `metrics_middleware`; share totals and pool utilization
+/// are sampled from the database when metrics are scraped.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub http_request_size: HistogramVec,
+    pub sync_items: Histogram,
+    pub shares_created: IntCounter,
+    pub shares_removed: IntCounter,
+    pub shares: IntGauge,
+    pub share_storage_bytes: IntGauge,
+    pub db_pool_connections: IntGaugeVec,
+    pub db_pool_max_connections: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            opts("http_requests_total", "HTTP requests by method, route and status"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration = HistogramVec::new(
+            histogram_opts(
+                "http_request_duration_seconds",
+                "HTTP request latency by method, route and status",
+                LATENCY_BUCKETS,
+            ),
+            &["method", "route", "status"],
+        )?;
+        let http_request_size = HistogramVec::new(
+            histogram_opts(
+                "http_request_size_bytes",
+                "HTTP request body size by method and route",
+                SIZE_BUCKETS,
+            ),
+            &["method", "route"],
+        )?;
+        let sync_items = Histogram::with_opts(histogram_opts(
+            "sync_items",
+            "Number of items per sync request",
+            ITEM_BUCKETS,
+        ))?;
+        let shares_created = IntCounter::with_opts(opts(
+            "shares_created_total",
+            "Shares created since start",
+        ))?;
+        let shares_removed = IntCounter::with_opts(opts(
+            "shares_removed_total",
+            "Shares removed since start",
+        ))?;
+        let shares = IntGauge::with_opts(opts("shares", "Shares currently stored"))?;
+        let share_storage_bytes = IntGauge::with_opts(opts(
+            "share_storage_bytes",
+            "Total serialized size of stored share data",
+        ))?;
+        let db_pool_connections = IntGaugeVec::new(
+            opts("db_pool_connections", "Database pool connections by state"),
+            &["state"],
+        )?;
+        let db_pool_max_connections = IntGauge::with_opts(opts(
+            "db_pool_max_connections",
+            "Configured maximum database pool size",
+        ))?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(http_request_size.clone()))?;
+        registry.register(Box::new(sync_items.clone()))?;
+        registry.register(Box::new(shares_created.clone()))?;
+        registry.register(Box::new(shares_removed.clone()))?;
+        registry.register(Box::new(shares.clone()))?;
+        registry.register(Box::new(share_storage_bytes.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(db_pool_max_connections.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            http_request_size,
+            sync_items,
+            shares_created,
+            shares_removed,
+            shares,
+            share_storage_bytes,
+            db_pool_connections,
+            db_pool_max_connections,
+        })
+    }
+
+    /// Record a completed HTTP request. `route` is the matched route template.
+    pub fn observe_request(
+        &self,
+        method: &str,
+        route: &str,
+        status: u16,
+        duration: Duration,
+        body_bytes: Option<u64>,
+    ) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+
+        self.http_requests.with_label_values(&labels).inc();
+        self.http_request_duration
+            .with_label_values(&labels)
+            .observe(duration.as_secs_f64());
+        if let Some(bytes) = body_bytes {
+            self.http_request_size
+                .with_label_values(&[method, route])
+                .observe(bytes as f64);
+        }
+    }
+
+    /// Sample share totals and pool utilization from the database.
+    pub async fn refresh(&self, pool: &PgPool, max_connections: u32) -> Result<()> {
+        let size = i64::from(pool.size());
+        let idle = pool.num_idle() as i64;
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle);
+        self.db_pool_connections
+            .with_label_values(&["active"])
+            .set(size - idle);
+        self.db_pool_max_connections.set(i64::from(max_connections));
+
+        let (count, bytes): (i64, i64) = sqlx::query_as(
+            "SELECT COUNT(*), COALESCE(SUM(storage_bytes), 0)::BIGINT FROM shares",
+        )
+        .fetch_one(pool)
+        .await?;
+        self.shares.set(count);
+        self.share_storage_bytes.set(bytes);
+
+        Ok(())
+    }
+
+    /// Render all metrics in the Prometheus text format.
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+fn opts(name: &str, help: &str) -> Opts {
+    Opts::new(name, help).namespace(NAMESPACE)
+}
+
+fn histogram_opts(name: &str, help: &str, buckets: &[f64]) -> HistogramOpts {
+    HistogramOpts::new(name, help)
+        .namespace(NAMESPACE)
+        .buckets(buckets.to_vec())
+}
@@ -1,27 +1,114 @@
+use anyhow::{Context, Result};
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    body::HttpBody,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
     middleware::Next,
     response::Response,
 };
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Instant;
-use tracing::{info, warn};
+use tracing::{event, info, info_span, warn, Instrument, Level};
+use uuid::Uuid;
 
+use crate::core::analytics::ViewKind;
+use crate::metrics::UNMATCHED_ROUTE;
+use crate::routes::health::HEALTH_PATHS;
+use crate::settings::LogFormat;
+use crate::telemetry;
 use crate::AppState;
 
+/// 请求ID头：沿用客户端提供的值或由服务端生成，并在响应中返回
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 当前请求的ID，作为请求扩展供后续处理器使用
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// 由中间件解析出的客户端IP，作为请求扩展供后续处理器使用
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+/// 受信任的反向代理网段，只有来自这些地址的转发头才会被采信
+///
+/// 通过 `server.trusted_proxies` 配置，CIDR 或单个 IP，
+/// 例如 `["127.0.0.1", "10.0.0.0/8", "::1"]`
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpNet>) -> Self {
+        Self { networks }
+    }
+
+    /// 解析逗号分隔的 CIDR / IP 列表
+    pub fn parse(list: &str) -> Result<Self> {
+        Self::from_list(list.split(','))
+    }
+
+    /// 解析 CIDR / IP 列表
+    pub fn from_list<I, S>(list: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let networks = list
+            .into_iter()
+            .map(|s| s.as_ref().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse::<IpNet>()
+                    .or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+                    .with_context(|| format!("Invalid trusted proxy address: {}", s))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { networks })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks.iter().any(|net| net.contains(&ip))
+    }
+}
+
 /// 记录HTTP请求访问日志的中间件
+///
+/// 为每个请求分配请求ID（沿用合法的 `X-Request-Id` 请求头，否则生成新的 UUID），
+/// 写入响应头，并在 `request` span 中执行后续处理，使其中所有日志都带有该ID。
+/// `logging.format = "json"` 时每个请求输出一条字段固定的结构化日志
 pub async fn access_log_middleware(
-    State(_state): State<AppState>,
-    request: Request,
+    State(state): State<AppState>,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     let start_time = Instant::now();
     let method = request.method().clone();
     let uri = request.uri().clone();
     let headers = request.headers().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let peer_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    // 沿用或生成请求ID
+    let request_id = incoming_request_id(&headers).unwrap_or_else(|| Uuid::new_v4().to_string());
+    request.extensions_mut().insert(RequestId(request_id.clone()));
     
     // 提取客户端IP
-    let client_ip = get_client_ip(&headers);
+    let client_ip = get_client_ip(&headers, peer_ip, &state.trusted_proxies);
+    request.extensions_mut().insert(ClientIp(client_ip.clone()));
+
+    // 健康检查探针请求频繁，仅在失败时记录
+    let is_probe = HEALTH_PATHS.contains(&uri.path());
+    let json = state.settings.logging.format == LogFormat::Json;
     
     // 提取User-Agent
     let user_agent = headers
@@ -30,21 +117,79 @@ pub async fn access_log_middleware(
         .unwrap_or("Unknown");
     
     // 记录请求开始
-    info!(
-        "📥 {} {} - IP: {} - User-Agent: {}",
-        method,
-        uri,
-        client_ip,
-        user_agent
-    );
+    if !is_probe && !json {
+        info!(
+            "📥 {} {} - IP: {} - User-Agent: {}",
+            method,
+            uri,
+            client_ip,
+            user_agent
+        );
+    }
     
-    // 执行请求
-    let response = next.run(request).await;
+    // 在带有请求ID的 span 中执行请求
+    let span_route = route.as_deref().unwrap_or(UNMATCHED_ROUTE);
+    let span = info_span!(
+        "request",
+        otel.name = %format!("{} {}", method, span_route),
+        otel.kind = "server",
+        request_id = %request_id,
+        method = %method,
+        route = span_route,
+    );
+    telemetry::set_remote_parent(&span, &headers);
+    let mut response = next.run(request).instrument(span).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
     
     // 计算处理时间
     let duration = start_time.elapsed();
     let status = response.status();
     let status_code = status.as_u16();
+
+    // 记录分享页面浏览与数据读取
+    if state.settings.analytics.enabled && method == Method::GET && status.is_success() {
+        record_share_view(&state, route.as_deref(), uri.path(), &headers, &client_ip);
+    }
+
+    if is_probe && status.is_success() {
+        return Ok(response);
+    }
+
+    if json {
+        let route = route.as_deref().unwrap_or(UNMATCHED_ROUTE);
+        let share_id = route_param(route, uri.path(), "share_id");
+        let bytes = response.body().size_hint().exact();
+        let latency_ms = duration.as_secs_f64() * 1000.0;
+
+        macro_rules! access_event {
+            ($level:expr) => {
+                event!(
+                    target: "access_log",
+                    $level,
+                    request_id = %request_id,
+                    method = %method,
+                    route = route,
+                    share_id = share_id,
+                    status = status_code,
+                    latency_ms = latency_ms,
+                    bytes = bytes,
+                    ip = %client_ip,
+                    user_agent = user_agent,
+                    "request completed"
+                )
+            };
+        }
+
+        match status_code {
+            400..=499 => access_event!(Level::WARN),
+            500..=599 => access_event!(Level::ERROR),
+            _ => access_event!(Level::INFO),
+        }
+
+        return Ok(response);
+    }
     
     // 根据状态码选择日志级别和图标
     let (log_icon, log_level) = match status_code {
@@ -71,18 +216,18 @@ pub async fn access_log_middleware(
             log_icon,
             method,
             uri,
-            status_code,
+            client_ip,
             duration,
-            client_ip
+            status_code
         ),
         "error" => tracing::error!(
             "{} {} {} - IP: {} - Duration: {:?} - Status: {}",
             log_icon,
             method,
             uri,
-            status_code,
+            client_ip,
             duration,
-            client_ip
+            status_code
         ),
         _ => {}
     }
@@ -90,30 +235,205 @@ pub async fn access_log_middleware(
     Ok(response)
 }
 
-/// 从请求头中提取客户端IP地址
-fn get_client_ip(headers: &HeaderMap) -> String {
-    // 尝试从各种头部获取真实IP
+/// 计入分享浏览统计的路由模板
+const VIEW_ROUTES: &[(&str, ViewKind)] = &[
+    ("/share/:share_id", ViewKind::Page),
+    ("/api/share/:share_id/data", ViewKind::Data),
+];
+
+/// 在后台写入一条分享浏览记录，不阻塞响应
+fn record_share_view(
+    state: &AppState,
+    route: Option<&str>,
+    path: &str,
+    headers: &HeaderMap,
+    client_ip: &str,
+) {
+    let Some((route, kind)) = route.and_then(|route| {
+        VIEW_ROUTES
+            .iter()
+            .find(|(view_route, _)| *view_route == route)
+            .map(|(view_route, kind)| (*view_route, *kind))
+    }) else {
+        return;
+    };
+    let Some(share_id) = route_param(route, path, "share_id") else {
+        return;
+    };
+
+    let share_id = share_id.to_string();
+    let client_ip = client_ip.to_string();
+    let user_agent = header_string(headers, header::USER_AGENT);
+    let referrer = header_string(headers, header::REFERER);
+    let view_service = state.view_service();
+
+    tokio::spawn(async move {
+        if let Err(e) = view_service
+            .record(
+                &share_id,
+                kind,
+                &client_ip,
+                user_agent.as_deref(),
+                referrer.as_deref(),
+            )
+            .await
+        {
+            warn!("⚠️ Failed to record share view - ID: {} - Error: {}", share_id, e);
+        }
+    });
+}
+
+fn header_string(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string)
+}
+
+/// 读取客户端提供的请求ID，仅接受长度不超过 128 的可见 ASCII 字符
+fn incoming_request_id(headers: &HeaderMap) -> Option<String> {
     headers
-        .get("x-forwarded-for")
+        .get(REQUEST_ID_HEADER)
         .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.split(',').next())
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .or_else(|| {
-            headers
-                .get("x-real-ip")
-                .and_then(|h| h.to_str().ok())
-        })
-        .or_else(|| {
-            headers
-                .get("cf-connecting-ip") // Cloudflare
-                .and_then(|h| h.to_str().ok())
-        })
-        .or_else(|| {
-            headers
-                .get("x-client-ip")
-                .and_then(|h| h.to_str().ok())
-        })
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| "Unknown".to_string())
-}
\ No newline at end of file
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+}
+
+/// 按路由模板从实际路径中取出指定参数，例如从 `/api/share/:share_id/sync` 中取 `share_id`
+fn route_param<'a>(route: &str, path: &'a str, name: &str) -> Option<&'a str> {
+    route
+        .split('/')
+        .zip(path.split('/'))
+        .find(|(segment, _)| segment.strip_prefix(':') == Some(name))
+        .map(|(_, value)| value)
+}
+
+/// 记录请求数、耗时与请求体大小的 Prometheus 指标中间件
+///
+/// 以路由模板（如 `/api/share/:share_id/sync`）作为标签，避免按分享 ID 产生大量时间序列
+pub async fn metrics_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start_time = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| route_label(path.as_str()))
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let body_bytes = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok());
+
+    let response = next.run(request).await;
+
+    state.metrics.observe_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        start_time.elapsed(),
+        body_bytes,
+    );
+
+    response
+}
+
+/// 管理接口鉴权中间件
+///
+/// 要求 `Authorization: Bearer <admin.token>`；未配置令牌时管理接口整体关闭并返回 404，
+/// 令牌缺失或错误返回 401
+pub async fn admin_auth_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(token) = state.settings.admin.token.as_deref().filter(|t| !t.is_empty()) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    if !constant_time_eq(provided.as_bytes(), token.as_bytes()) {
+        warn!("⚠️ Rejected admin request - Path: {}", request.uri().path());
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// 按固定时间比较，避免通过响应耗时逐字节猜测令牌
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// 将 axum 内部的嵌套通配参数名替换为 `*`
+fn route_label(path: &str) -> String {
+    match path.split_once("/*") {
+        Some((prefix, _)) => format!("{}/*", prefix),
+        None => path.to_string(),
+    }
+}
+
+/// 提取客户端IP地址
+///
+/// 仅当对端地址属于受信任代理时才采信转发头：`X-Forwarded-For` 从右向左
+/// 跳过受信任的代理节点，取第一个不受信任的地址；遇到无法解析的节点时
+/// 停止并返回对端地址；否则返回对端地址本身
+pub fn get_client_ip(
+    headers: &HeaderMap,
+    peer_ip: Option<IpAddr>,
+    trusted_proxies: &TrustedProxies,
+) -> String {
+    let peer_ip = match peer_ip {
+        Some(ip) => ip.to_canonical(),
+        None => return "Unknown".to_string(),
+    };
+
+    if !trusted_proxies.contains(peer_ip) {
+        return peer_ip.to_string();
+    }
+
+    // 从右向左遍历 X-Forwarded-For，跳过受信任的代理
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .flat_map(|h| h.to_str().unwrap_or("").split(','))
+        .map(str::trim)
+        .collect();
+
+    let mut client = None;
+    for hop in hops.iter().rev() {
+        // 无法解析的节点无法验证，其左侧内容均可能由客户端伪造
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            return peer_ip.to_string();
+        };
+        client = Some(ip);
+        if !trusted_proxies.contains(ip) {
+            break;
+        }
+    }
+    // 全部为受信任代理时为最左侧地址
+    if let Some(client) = client {
+        return client.to_canonical().to_string();
+    }
+
+    // 单值头部，仅由受信任代理设置
+    ["x-real-ip", "cf-connecting-ip", "x-client-ip"]
+        .iter()
+        .filter_map(|name| headers.get(*name))
+        .filter_map(|h| h.to_str().ok())
+        .find_map(|s| s.trim().parse::<IpAddr>().ok())
+        .unwrap_or(peer_ip)
+        .to_canonical()
+        .to_string()
+}
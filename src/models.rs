@@ -1,6 +1,7 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Share {
@@ -8,48 +9,96 @@ pub struct Share {
     pub secret: String,
     pub session_id: String,
     pub data: Option<Value>, // JSONB field storing current state as array
+    pub storage_bytes: i64,  // Serialized size of `data`, used for quotas
+    pub redactions: Value,   // Per-item redaction counts: { item_key: { rule: count } }
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-/// ShareData is now an arbitrary JSON value.
-/// Clients should include a `_key` field to identify how data should be merged.
-/// Optional `_type` field can be used by clients for their own type identification.
-///
-/// Example:
-/// ```json
-/// {
-///   "_key": "session",
-///   "_type": "session",
-///   "id": "xxx",
-///   "title": "...",
-///   ... // any other fields
-/// }
-/// ```
-pub type ShareData = Value;
-
-// Create share request
-#[derive(Debug, Deserialize)]
-pub struct CreateShareRequest {
-    #[serde(rename = "sessionID")]
+/// A share without its data, for listings.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ShareSummary {
+    pub id: String,
     pub session_id: String,
+    /// Title of the stored session item, if any
+    pub title: Option<String>,
+    pub storage_bytes: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
-// Create share response
+/// Column admin share listings are sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareSort {
+    Created,
+    #[default]
+    Updated,
+    /// Stored data size (`storage_bytes`)
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+// Share listing query
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShareListQuery {
+    pub sort: ShareSort,
+    pub order: SortOrder,
+    pub limit: i64,
+    pub offset: i64,
+    /// Only list shares of this session
+    pub session_id: Option<String>,
+}
+
+impl Default for ShareListQuery {
+    fn default() -> Self {
+        Self {
+            sort: ShareSort::default(),
+            order: SortOrder::default(),
+            limit: 50,
+            offset: 0,
+            session_id: None,
+        }
+    }
+}
+
+// Share listing response
 #[derive(Debug, Serialize)]
-pub struct CreateShareResponse {
-    pub id: String,
-    pub secret: String,
-    pub url: String,
+pub struct ShareListResponse {
+    pub shares: Vec<ShareSummary>,
+    /// Shares matching the query across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
 }
 
-// Sync share request
-#[derive(Debug, Deserialize)]
-pub struct SyncShareRequest {
-    pub secret: String,
-    pub data: Vec<ShareData>,
+// Storage totals response
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct StorageTotals {
+    pub shares: i64,
+    /// Sum of `storage_bytes` over all shares
+    pub storage_bytes: i64,
+    pub largest_share_bytes: i64,
+    /// Shares that never received any data
+    pub empty_shares: i64,
+    pub views: i64,
+    /// On-disk size of the share tables, including indexes and TOAST
+    pub database_bytes: i64,
 }
 
+// Wire types shared with the client library
+pub use opencode_share_client::models::{
+    CreateShareRequest, CreateShareResponse, RemoveShareRequest, ShareData, SyncShareRequest,
+};
+
 // Sync share response
 #[derive(Debug, Serialize)]
 pub struct SyncShareResponse {
@@ -62,8 +111,104 @@ pub struct GetShareResponse {
     pub data: Vec<ShareData>,
 }
 
-// Remove share request
+// Redaction report response
+#[derive(Debug, Serialize)]
+pub struct RedactionReportResponse {
+    pub total: usize,
+    pub items: Value,
+}
+
+// Liveness / readiness response
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<HealthChecks>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthChecks {
+    pub database: String,
+    pub migrations: String,
+}
+
+// Version response
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub name: String,
+    pub version: String,
+    pub git_sha: String,
+    pub features: Vec<String>,
+}
+
+// Share view statistics query
 #[derive(Debug, Deserialize)]
-pub struct RemoveShareRequest {
-    pub secret: String,
-}
\ No newline at end of file
+pub struct StatsQuery {
+    /// Days of daily counts to return
+    pub days: Option<u32>,
+}
+
+// Share view statistics response
+#[derive(Debug, Serialize)]
+pub struct ShareStatsResponse {
+    pub total_views: i64,
+    pub unique_visitors: i64,
+    pub page_views: i64,
+    pub data_fetches: i64,
+    pub first_viewed_at: Option<DateTime<Utc>>,
+    pub last_viewed_at: Option<DateTime<Utc>>,
+    pub daily: Vec<DailyViews>,
+    pub user_agents: BTreeMap<String, i64>,
+    pub referrers: Vec<ReferrerViews>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyViews {
+    pub date: NaiveDate,
+    pub views: i64,
+    pub unique_visitors: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferrerViews {
+    pub referrer: String,
+    pub views: i64,
+}
+
+// Share embed query
+#[derive(Debug, Deserialize)]
+pub struct EmbedQuery {
+    /// First message shown, 1-based
+    pub from: Option<usize>,
+    /// Last message shown, inclusive
+    pub to: Option<usize>,
+}
+
+// oEmbed request (https://oembed.com/#section2.2)
+#[derive(Debug, Deserialize)]
+pub struct OEmbedQuery {
+    pub url: String,
+    pub maxwidth: Option<u32>,
+    pub maxheight: Option<u32>,
+    pub format: Option<String>,
+}
+
+// oEmbed "rich" response
+#[derive(Debug, Serialize)]
+pub struct OEmbedResponse {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub version: String,
+    pub title: String,
+    pub provider_name: String,
+    pub provider_url: String,
+    pub html: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_height: Option<u32>,
+}
@@ -0,0 +1,178 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get},
+    Router,
+};
+use chrono::Utc;
+use std::io;
+use tokio::sync::oneshot;
+use futures_util::{future, stream, StreamExt, TryStreamExt};
+use tokio_util::io::{ReaderStream, StreamReader};
+use tracing::{error, info, warn};
+
+use crate::core::archive::{export_archive, import_archive, ImportSummary, InvalidArchive};
+use crate::models::{ShareListQuery, ShareListResponse, StorageTotals};
+use crate::AppState;
+
+/// Buffer between the archive writer task and the response body.
+const ARCHIVE_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Most shares returned per listing page.
+const MAX_LIST_LIMIT: i64 = 500;
+
+/// Operator endpoints, mounted under `/admin/api` behind `admin_auth_middleware`.
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/shares", get(list_shares))
+        .route("/shares/:share_id", delete(delete_share))
+        .route("/stats", get(storage_stats))
+        .route("/archive", get(download_archive).post(upload_archive))
+}
+
+/// A page of shares, sorted by `created`, `updated` or `size` and optionally filtered by
+/// `session_id`.
+pub async fn list_shares(
+    State(state): State<AppState>,
+    Query(mut query): Query<ShareListQuery>,
+) -> Result<Json<ShareListResponse>, StatusCode> {
+    query.limit = query.limit.clamp(1, MAX_LIST_LIMIT);
+    query.offset = query.offset.max(0);
+
+    info!(
+        "📋 Listing shares - Sort: {:?} {:?} - Limit: {} - Offset: {}",
+        query.sort, query.order, query.limit, query.offset
+    );
+
+    let share_service = state.share_service();
+    let result = tokio::try_join!(
+        share_service.list(&query),
+        share_service.count(query.session_id.as_deref()),
+    );
+
+    match result {
+        Ok((shares, total)) => Ok(Json(ShareListResponse {
+            shares,
+            total,
+            limit: query.limit,
+            offset: query.offset,
+        })),
+        Err(e) => {
+            error!("❌ Failed to list shares - Error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Delete a share without its secret.
+pub async fn delete_share(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+) -> Result<(), StatusCode> {
+    info!("🗑️ Force-deleting share - ID: {}", share_id);
+
+    match state.share_service().delete(&share_id).await {
+        Ok(true) => {
+            state.metrics.shares_removed.inc();
+            info!("✅ Force-deleted share - ID: {}", share_id);
+            Ok(())
+        }
+        Ok(false) => {
+            warn!("⚠️ Share not found - ID: {}", share_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!(
+                "❌ Failed to force-delete share - ID: {} - Error: {}",
+                share_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Share counts and storage used across the server.
+pub async fn storage_stats(State(state): State<AppState>) -> Result<Json<StorageTotals>, StatusCode> {
+    info!("📊 Computing storage totals");
+
+    state.share_service().storage_totals().await.map(Json).map_err(|e| {
+        error!("❌ Failed to compute storage totals - Error: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Stream every share as a gzip-compressed NDJSON archive for backup or migration.
+///
+/// If the export fails partway, the body ends with an error so the chunked transfer is
+/// aborted instead of completing with a truncated archive.
+pub async fn download_archive(State(state): State<AppState>) -> Response {
+    info!("📦 Exporting share archive");
+
+    let (writer, reader) = tokio::io::duplex(ARCHIVE_BUFFER_BYTES);
+    let (done_tx, done_rx) = oneshot::channel();
+    let pool = state.db.clone();
+    tokio::spawn(async move {
+        let result = export_archive(&pool, writer).await;
+        match &result {
+            Ok(count) => info!("✅ Share archive exported - Shares: {}", count),
+            Err(e) => error!("❌ Failed to export share archive - Error: {}", e),
+        }
+        let _ = done_tx.send(result.map(|_| ()).map_err(|e| e.to_string()));
+    });
+
+    // Checked once the writer is dropped and everything written has been sent
+    let outcome = stream::once(async move {
+        match done_rx.await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(Err(io::Error::other(e))),
+            Err(_) => Some(Err(io::Error::other("Share archive export stopped"))),
+        }
+    })
+    .filter_map(future::ready);
+
+    let filename = format!(
+        "opencode-share-{}.ndjson.gz",
+        Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    (
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(ReaderStream::new(reader).chain(outcome)),
+    )
+        .into_response()
+}
+
+/// Import an archive produced by `GET /admin/api/archive`; safe to repeat.
+pub async fn upload_archive(
+    State(state): State<AppState>,
+    body: Body,
+) -> Result<Json<ImportSummary>, StatusCode> {
+    info!("📦 Importing share archive");
+
+    let reader = StreamReader::new(body.into_data_stream().map_err(std::io::Error::other));
+
+    match import_archive(&state.db, reader).await {
+        Ok(summary) => {
+            info!(
+                "✅ Share archive imported - Created: {} - Updated: {} - Skipped: {}",
+                summary.created, summary.updated, summary.skipped
+            );
+            Ok(Json(summary))
+        }
+        Err(e) if e.downcast_ref::<InvalidArchive>().is_some() => {
+            warn!("⚠️ Rejected share archive - Error: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            error!("❌ Failed to import share archive - Error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
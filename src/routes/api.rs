@@ -1,26 +1,49 @@
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{delete, get, post},
     Router,
 };
 use serde_json::Value;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    core::share::ShareService,
-    models::{CreateShareRequest, CreateShareResponse, RemoveShareRequest, SyncShareRequest},
+    core::{
+        export::render_html_export,
+        import::SessionExport,
+        limits::LimitError,
+        markdown::render_markdown,
+        patch::{file_after, session_patch},
+        share::{InvalidSecret, ShareExists, ShareNotFound},
+        transcript::Transcript,
+    },
+    models::{
+        CreateShareRequest, CreateShareResponse, RedactionReportResponse, RemoveShareRequest,
+        ShareStatsResponse, StatsQuery, SyncShareRequest,
+    },
     AppState,
 };
 
+/// Header carrying the share secret on read-only endpoints that require it.
+pub const SHARE_SECRET_HEADER: &str = "x-share-secret";
 
+/// Days of daily view counts returned by the stats endpoint by default, and at most.
+const DEFAULT_STATS_DAYS: u32 = 30;
+const MAX_STATS_DAYS: u32 = 365;
 
 pub fn api_routes() -> Router<AppState> {
     Router::new()
         .route("/share", post(create_share))
+        .route("/share/import", post(import_share))
         .route("/share/:share_id/sync", post(sync_share))
         .route("/share/:share_id/data", get(get_share_data))
+        .route("/share/:share_id/redactions", get(get_share_redactions))
+        .route("/share/:share_id/stats", get(get_share_stats))
+        .route("/share/:share_id/export.md", get(export_share_markdown))
+        .route("/share/:share_id/export.html", get(export_share_html))
+        .route("/share/:share_id/diff.patch", get(get_share_patch))
+        .route("/share/:share_id/files/*path", get(get_share_file))
         .route("/share/:share_id", delete(remove_share))
 }
 
@@ -42,24 +65,24 @@ pub async fn create_share(
         user_agent
     );
     
-    let share_service = ShareService::new(state.db.clone());
+    // Resolve the public base URL before creating anything
+    let base_url = match state.public_url.base_url(&headers) {
+        Ok(base_url) => base_url,
+        Err(e) => {
+            warn!(
+                "⚠️ Rejected share creation - SessionID: {} - Error: {}",
+                session_id, e
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+    
+    let share_service = state.share_service();
     
     match share_service.create(request.session_id).await {
         Ok(share) => {
-            // Build URL similar to original
-            let protocol = headers
-                .get("x-forwarded-proto")
-                .or_else(|| headers.get("x-forwarded-protocol"))
-                .and_then(|h| h.to_str().ok())
-                .unwrap_or("https");
-            
-            let host = headers
-                .get("x-forwarded-host")
-                .or_else(|| headers.get("host"))
-                .and_then(|h| h.to_str().ok())
-                .unwrap_or("localhost:3000");
-
-            let url = format!("{protocol}://{host}/share/{}", share.id);
+            state.metrics.shares_created.inc();
+            let url = format!("{}/share/{}", base_url, share.id);
             
             info!(
                 "✅ Share created successfully - ID: {} - URL: {}",
@@ -82,6 +105,73 @@ pub async fn create_share(
     }
 }
 
+/// Publish a finished session from `opencode export` JSON as a new share.
+pub async fn import_share(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(export): Json<SessionExport>,
+) -> Result<Json<CreateShareResponse>, StatusCode> {
+    let Some(session_id) = export.session_id().map(str::to_string) else {
+        warn!("⚠️ Rejected share import - session export has no ID");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    info!(
+        "📥 Importing share - SessionID: {} - Messages: {}",
+        session_id,
+        export.messages.len()
+    );
+
+    let base_url = match state.public_url.base_url(&headers) {
+        Ok(base_url) => base_url,
+        Err(e) => {
+            warn!(
+                "⚠️ Rejected share import - SessionID: {} - Error: {}",
+                session_id, e
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    match state.share_service().import(export).await {
+        Ok(share) => {
+            state.metrics.shares_created.inc();
+            let url = format!("{}/share/{}", base_url, share.id);
+
+            info!(
+                "✅ Share imported successfully - ID: {} - URL: {}",
+                share.id, url
+            );
+
+            Ok(Json(CreateShareResponse {
+                id: share.id,
+                secret: share.secret,
+                url,
+            }))
+        }
+        Err(e) if e.downcast_ref::<ShareExists>().is_some() => {
+            warn!(
+                "⚠️ Rejected share import - SessionID: {} - Error: {}",
+                session_id, e
+            );
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) if e.downcast_ref::<LimitError>().is_some() => {
+            warn!(
+                "⚠️ Import rejected by size limits - SessionID: {} - Error: {}",
+                session_id, e
+            );
+            Err(StatusCode::PAYLOAD_TOO_LARGE)
+        }
+        Err(e) => {
+            error!(
+                 "❌ Failed to import share - SessionID: {} - Error: {}",
+                 session_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn sync_share(
     State(state): State<AppState>,
     Path(share_id): Path<String>,
@@ -95,16 +185,24 @@ pub async fn sync_share(
         share_id, data_size
     );
     
-    let share_service = ShareService::new(state.db.clone());
+    let share_service = state.share_service();
     
     match share_service.sync(&share_id, &request.secret, request.data).await {
         Ok(_) => {
+            state.metrics.sync_items.observe(data_size as f64);
             info!(
                 "✅ Successfully synced data to share - ID: {} - Data items: {}",
                 share_id, data_size
             );
             Ok(())
         }
+        Err(e) if e.downcast_ref::<LimitError>().is_some() => {
+            warn!(
+                "⚠️ Sync rejected by size limits - ID: {} - Error: {}",
+                share_id, e
+            );
+            Err(StatusCode::PAYLOAD_TOO_LARGE)
+        }
         Err(e) => {
             error!(
                  "❌ Failed to sync share - ID: {} - Error: {}",
@@ -126,7 +224,7 @@ pub async fn get_share_data(
         share_id
     );
     
-    let share_service = ShareService::new(state.db.clone());
+    let share_service = state.share_service();
     
     match share_service.get_data(&share_id).await {
         Ok(data) => {
@@ -147,6 +245,108 @@ pub async fn get_share_data(
     }
 }
 
+pub async fn get_share_redactions(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<RedactionReportResponse>, StatusCode> {
+    let secret = headers
+        .get(SHARE_SECRET_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    info!(
+        "🕵️ Retrieving redaction report - ID: {}",
+        share_id
+    );
+
+    let share_service = state.share_service();
+
+    match share_service.get_redactions(&share_id, secret).await {
+        Ok(items) => {
+            let total = items
+                .as_object()
+                .map(|map| {
+                    map.values()
+                        .filter_map(|counts| counts.as_object())
+                        .flat_map(|counts| counts.values())
+                        .filter_map(|count| count.as_u64())
+                        .sum::<u64>() as usize
+                })
+                .unwrap_or(0);
+
+            info!(
+                "✅ Retrieved redaction report - ID: {} - Redactions: {}",
+                share_id, total
+            );
+            Ok(Json(RedactionReportResponse { total, items }))
+        }
+        Err(e) if e.downcast_ref::<ShareNotFound>().is_some() => {
+            warn!("⚠️ Share not found - ID: {}", share_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) if e.downcast_ref::<InvalidSecret>().is_some() => {
+            warn!("⚠️ Invalid secret for redaction report - ID: {}", share_id);
+            Err(StatusCode::FORBIDDEN)
+        }
+        Err(e) => {
+            error!(
+                 "❌ Failed to get redaction report - ID: {} - Error: {}",
+                 share_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_share_stats(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+    Query(query): Query<StatsQuery>,
+    headers: HeaderMap,
+) -> Result<Json<ShareStatsResponse>, StatusCode> {
+    let secret = headers
+        .get(SHARE_SECRET_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_STATS_DAYS)
+        .clamp(1, MAX_STATS_DAYS);
+
+    info!(
+        "📊 Retrieving share stats - ID: {} - Days: {}",
+        share_id, days
+    );
+
+    let view_service = state.view_service();
+
+    match view_service.stats(&share_id, secret, days).await {
+        Ok(stats) => {
+            info!(
+                "✅ Retrieved share stats - ID: {} - Views: {} - Unique: {}",
+                share_id, stats.total_views, stats.unique_visitors
+            );
+            Ok(Json(stats))
+        }
+        Err(e) if e.downcast_ref::<ShareNotFound>().is_some() => {
+            warn!("⚠️ Share not found - ID: {}", share_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) if e.downcast_ref::<InvalidSecret>().is_some() => {
+            warn!("⚠️ Invalid secret for share stats - ID: {}", share_id);
+            Err(StatusCode::FORBIDDEN)
+        }
+        Err(e) => {
+            error!(
+                 "❌ Failed to get share stats - ID: {} - Error: {}",
+                 share_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn remove_share(
     State(state): State<AppState>,
     Path(share_id): Path<String>,
@@ -159,10 +359,11 @@ pub async fn remove_share(
         share_id
     );
     
-    let share_service = ShareService::new(state.db.clone());
+    let share_service = state.share_service();
     
     match share_service.remove(&share_id, &request.secret).await {
         Ok(_) => {
+            state.metrics.shares_removed.inc();
             info!(
                 "✅ Successfully removed share - ID: {}",
                 share_id
@@ -179,3 +380,143 @@ pub async fn remove_share(
     }
 }
 
+/// The share's conversation as Markdown, for pasting into PRs and docs.
+pub async fn export_share_markdown(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+) -> Result<Response, StatusCode> {
+    info!("📤 Exporting share as Markdown - ID: {}", share_id);
+
+    let items = load_items(&state, &share_id).await?;
+    let markdown = render_markdown(&Transcript::from_items(&items));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", download_filename(&share_id, "md")),
+            ),
+        ],
+        markdown,
+    )
+        .into_response())
+}
+
+/// The share page as a single HTML file with styles, script and the stored items inlined,
+/// so it renders offline, e.g. when attached to a ticket.
+pub async fn export_share_html(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    info!("📤 Exporting share as HTML - ID: {}", share_id);
+
+    let items = load_items(&state, &share_id).await?;
+    let base_url = state.public_url.base_url(&headers).ok();
+
+    let html = render_html_export(
+        &state.templates,
+        &state.settings.paths.static_dir,
+        &share_id,
+        &items,
+        base_url.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        error!("❌ Failed to render HTML export - ID: {} - Error: {:#}", share_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", download_filename(&share_id, "html")),
+            ),
+        ],
+        html,
+    )
+        .into_response())
+}
+
+/// The session's file changes as a patch for `git apply`.
+pub async fn get_share_patch(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+) -> Result<Response, StatusCode> {
+    info!("📤 Exporting share diff as patch - ID: {}", share_id);
+
+    let items = load_items(&state, &share_id).await?;
+    let transcript = Transcript::from_items(&items);
+    let patch = session_patch(&transcript.diffs, transcript.root());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/x-diff; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", download_filename(&share_id, "patch")),
+            ),
+        ],
+        patch,
+    )
+        .into_response())
+}
+
+/// Content of a changed file after the session.
+pub async fn get_share_file(
+    State(state): State<AppState>,
+    Path((share_id, path)): Path<(String, String)>,
+) -> Result<Response, StatusCode> {
+    info!("📄 Retrieving changed file - ID: {} - Path: {}", share_id, path);
+
+    let items = load_items(&state, &share_id).await?;
+    let transcript = Transcript::from_items(&items);
+    let Some(content) = file_after(&transcript.diffs, transcript.root(), &path) else {
+        warn!("⚠️ File not in share diff - ID: {} - Path: {}", share_id, path);
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        ],
+        content.to_string(),
+    )
+        .into_response())
+}
+
+/// Load a share's stored items, or 404 if the share does not exist.
+async fn load_items(state: &AppState, share_id: &str) -> Result<Vec<Value>, StatusCode> {
+    match state.share_service().get(share_id).await {
+        Ok(Some(share)) => match share.data {
+            Some(Value::Array(items)) => Ok(items),
+            _ => Ok(Vec::new()),
+        },
+        Ok(None) => {
+            warn!("⚠️ Share not found - ID: {}", share_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!(
+                "❌ Error checking share - ID: {} - Error: {}",
+                share_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// File name for a share download, keeping only `[A-Za-z0-9_-]` of the share ID so it
+/// cannot break out of the `Content-Disposition` header value.
+fn download_filename(share_id: &str, extension: &str) -> String {
+    let name: String = share_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect();
+    let name = if name.is_empty() { "share" } else { &name };
+    format!("{}.{}", name, extension)
+}
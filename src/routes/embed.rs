@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    routing::get,
+    Router,
+};
+use tracing::{error, info, warn};
+
+use crate::core::meta::ShareMeta;
+use crate::core::render::escape_html;
+use crate::core::transcript::Transcript;
+use crate::models::{OEmbedQuery, OEmbedResponse};
+use crate::AppState;
+
+pub fn embed_routes() -> Router<AppState> {
+    Router::new().route("/oembed", get(oembed))
+}
+
+/// oEmbed provider for share URLs, returning a `rich` embed of `/share/:id/embed`.
+///
+/// Only JSON is supported; `format=xml` gets 501 as the spec requires.
+pub async fn oembed(
+    State(state): State<AppState>,
+    Query(query): Query<OEmbedQuery>,
+    headers: HeaderMap,
+) -> Result<Json<OEmbedResponse>, StatusCode> {
+    info!("🌐 oEmbed request - URL: {}", query.url);
+
+    if query.format.as_deref().is_some_and(|format| format != "json") {
+        return Err(StatusCode::NOT_IMPLEMENTED);
+    }
+
+    let base_url = state.public_url.base_url(&headers).map_err(|e| {
+        warn!("⚠️ oEmbed request rejected - Error: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let Some(share_id) = share_id_from_url(&query.url, &base_url) else {
+        warn!("⚠️ oEmbed URL is not a share - URL: {}", query.url);
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let share = match state.share_service().get(&share_id).await {
+        Ok(Some(share)) => share,
+        Ok(None) => {
+            warn!("⚠️ Share not found for oEmbed - ID: {}", share_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(
+                "❌ Error checking share - ID: {} - Error: {}",
+                share_id, e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let items = share
+        .data
+        .as_ref()
+        .and_then(|data| data.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let meta = ShareMeta::new(&Transcript::from_items(items), &share_id, Some(&base_url));
+
+    let embed = &state.settings.embed;
+    let width = query.maxwidth.map_or(embed.width, |max| embed.width.min(max));
+    let height = query.maxheight.map_or(embed.height, |max| embed.height.min(max));
+    let html = format!(
+        r#"<iframe src="{}/share/{}/embed" width="{}" height="{}" title="{}" frameborder="0" loading="lazy"></iframe>"#,
+        base_url,
+        escape_html(&share_id),
+        width,
+        height,
+        escape_html(&meta.title),
+    );
+
+    Ok(Json(OEmbedResponse {
+        kind: "rich".to_string(),
+        version: "1.0".to_string(),
+        title: meta.title,
+        provider_name: "opencode".to_string(),
+        provider_url: base_url,
+        html,
+        width,
+        height,
+        thumbnail_url: meta.image,
+        thumbnail_width: Some(meta.image_width),
+        thumbnail_height: Some(meta.image_height),
+    }))
+}
+
+/// Share id of `url` if it is a share page (or its embed view) on this server.
+///
+/// The scheme is not compared, so `http://` links to an `https://` deployment still match.
+fn share_id_from_url(url: &str, base_url: &str) -> Option<String> {
+    let strip_scheme = |url: &str| {
+        url.strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .map(str::to_string)
+    };
+    let url = strip_scheme(url)?;
+    let base = strip_scheme(base_url)?;
+
+    let path = url
+        .get(..base.len())
+        .filter(|host| host.eq_ignore_ascii_case(&base))
+        .map(|_| &url[base.len()..])?;
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let rest = path.strip_prefix("/share/")?;
+    let rest = rest.trim_end_matches('/');
+    let share_id = rest.strip_suffix("/embed").unwrap_or(rest);
+
+    let valid = !share_id.is_empty()
+        && share_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| share_id.to_string())
+}
@@ -0,0 +1,113 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use tracing::{error, warn};
+
+use crate::{
+    database,
+    metrics::METRICS_CONTENT_TYPE,
+    models::{HealthChecks, HealthResponse, VersionResponse},
+    AppState,
+};
+
+/// Paths served by this router; successful requests to them are not access-logged.
+pub const HEALTH_PATHS: &[&str] = &["/healthz", "/readyz", "/version", "/metrics"];
+
+pub fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/version", get(version))
+        .route("/metrics", get(metrics))
+}
+
+/// Liveness: the process is up and serving requests.
+pub async fn healthz() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        checks: None,
+    })
+}
+
+/// Readiness: the database is reachable and all embedded migrations are applied.
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let database = match database::ping(&state.db).await {
+        Ok(()) => "ok".to_string(),
+        Err(e) => {
+            error!("❌ Readiness check failed - database unreachable - Error: {}", e);
+            "unreachable".to_string()
+        }
+    };
+
+    let migrations = if database != "ok" {
+        "unknown".to_string()
+    } else {
+        match database::pending_migrations(&state.db).await {
+            Ok(pending) if pending.is_empty() => "ok".to_string(),
+            Ok(pending) => {
+                warn!("⚠️ Readiness check failed - pending migrations: {:?}", pending);
+                "pending".to_string()
+            }
+            Err(e) => {
+                error!("❌ Readiness check failed - migrations unknown - Error: {}", e);
+                "unknown".to_string()
+            }
+        }
+    };
+
+    let ready = database == "ok" && migrations == "ok";
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(HealthResponse {
+            status: if ready { "ready" } else { "not_ready" }.to_string(),
+            checks: Some(HealthChecks {
+                database,
+                migrations,
+            }),
+        }),
+    )
+}
+
+/// Build information baked in at compile time.
+pub async fn version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("GIT_SHA").to_string(),
+        features: env!("ENABLED_FEATURES")
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Prometheus metrics in the text exposition format.
+pub async fn metrics(State(state): State<AppState>) -> Result<impl IntoResponse, StatusCode> {
+    // Stale database gauges are better than no metrics at all
+    if let Err(e) = state
+        .metrics
+        .refresh(&state.db, state.settings.database.max_connections)
+        .await
+    {
+        warn!("⚠️ Failed to refresh database metrics - Error: {}", e);
+    }
+
+    match state.metrics.render() {
+        Ok(body) => Ok(([(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)], body)),
+        Err(e) => {
+            error!("❌ Failed to render metrics - Error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,5 +1,11 @@
+pub mod admin;
 pub mod api;
+pub mod embed;
+pub mod health;
 pub mod share;
 
+pub use admin::admin_routes;
 pub use api::api_routes;
+pub use embed::embed_routes;
+pub use health::health_routes;
 pub use share::share_routes;
\ No newline at end of file
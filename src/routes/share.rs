@@ -1,19 +1,25 @@
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    response::Html,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
     routing::get,
     Router,
 };
-use std::fs;
-use std::path::PathBuf;
+use minijinja::context;
 use tracing::{error, info, warn};
 
-use crate::core::share::ShareService;
+use crate::core::meta::ShareMeta;
+use crate::core::render::{render_embed, render_transcript, EmbedOptions};
+use crate::core::transcript::Transcript;
+use crate::models::EmbedQuery;
+use crate::templates::{EMBED_TEMPLATE, SHARE_TEMPLATE};
 use crate::AppState;
 
 pub fn share_routes() -> Router<AppState> {
-    Router::new().route("/:share_id", get(share_page))
+    Router::new()
+        .route("/:share_id", get(share_page))
+        .route("/:share_id/og.png", get(share_og_image))
+        .route("/:share_id/embed", get(share_embed))
 }
 
 pub async fn share_page(
@@ -32,18 +38,39 @@ pub async fn share_page(
         share_id
     );
     
-    let share_service = ShareService::new(state.db.clone());
+    let share_service = state.share_service();
     
     // Verify share exists
     match share_service.get(&share_id).await {
-        Ok(Some(_share)) => {
+        Ok(Some(share)) => {
+            let items = share
+                .data
+                .as_ref()
+                .and_then(|data| data.as_array())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let transcript = Transcript::from_items(items);
+            let content = render_transcript(&transcript);
+
+            // Link previews need absolute URLs; leave them out for disallowed hosts
+            let base_url = state.public_url.base_url(&headers).ok();
+            let meta = ShareMeta::new(&transcript, &share_id, base_url.as_deref());
+
+            let html = state
+                .templates
+                .render(
+                    SHARE_TEMPLATE,
+                    context! { share_id => &share_id, content => content, meta => meta },
+                )
+                .map_err(|e| {
+                    error!("Failed to render share page - ID: {} - Error: {:#}", share_id, e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
             info!(
                 "✅ Share page rendered successfully - ID: {}",
                 share_id
             );
-            
-            // Return HTML page using template
-            let html = generate_share_page(&share_id)?;
             Ok(Html(html))
         }
         Ok(None) => {
@@ -63,27 +90,151 @@ pub async fn share_page(
     }
 }
 
-fn generate_share_page(share_id: &str) -> Result<String, StatusCode> {
-    let template_path = PathBuf::from("templates/share.html");
+/// Compact view of a share for iframes, optionally limited to a range of messages.
+///
+/// Framing is restricted by a `frame-ancestors` policy built from `embed.frame_ancestors`.
+pub async fn share_embed(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+    Query(query): Query<EmbedQuery>,
+) -> Result<Response, StatusCode> {
+    info!("🌐 Share embed request - ID: {}", share_id);
+
+    let share = match state.share_service().get(&share_id).await {
+        Ok(Some(share)) => share,
+        Ok(None) => {
+            warn!("⚠️ Share not found for embed - ID: {}", share_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(
+                "❌ Error checking share - ID: {} - Error: {}",
+                share_id, e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let items = share
+        .data
+        .as_ref()
+        .and_then(|data| data.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let options = EmbedOptions {
+        share_url: format!("/share/{}", share_id),
+        from: query.from,
+        to: query.to,
+    };
+    let content = render_embed(&Transcript::from_items(items), &options);
 
-    // Read HTML template file
-    let template_content = fs::read_to_string(template_path)
+    let html = state
+        .templates
+        .render(
+            EMBED_TEMPLATE,
+            context! { share_id => &share_id, content => content },
+        )
         .map_err(|e| {
-            error!("Failed to read HTML template: {}", e);
+            error!("Failed to render share embed - ID: {} - Error: {:#}", share_id, e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    // HTML escape the share_id to prevent XSS
-    let escaped_share_id = share_id
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#39;");
+    let policy = format!(
+        "frame-ancestors {}",
+        state.settings.embed.frame_ancestors.join(" ")
+    );
+    Ok(([(header::CONTENT_SECURITY_POLICY, policy)], Html(html)).into_response())
+}
 
-    // Replace {{share_id}} placeholder with escaped share_id
-    let html = template_content.replace("{{share_id}}", &escaped_share_id);
+/// How long link unfurlers may reuse a preview image before revalidating.
+const OG_IMAGE_MAX_AGE_SECS: u32 = 300;
 
-    Ok(html)
-}
+/// Social preview image for a share, regenerated whenever the share is synced.
+pub async fn share_og_image(
+    State(state): State<AppState>,
+    Path(share_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let share_service = state.share_service();
+
+    let share = match share_service.get(&share_id).await {
+        Ok(Some(share)) => share,
+        Ok(None) => {
+            warn!("⚠️ Share not found for preview image - ID: {}", share_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(
+                "❌ Error checking share - ID: {} - Error: {}",
+                share_id, e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // The share's last sync time identifies the revision the image was drawn from
+    let revision = share.updated_at;
+    let etag = format!("\"{}\"", revision.timestamp_micros());
+    let cache_control = format!("public, max-age={}", OG_IMAGE_MAX_AGE_SECS);
 
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag));
+    if not_modified {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::CACHE_CONTROL, cache_control)],
+        )
+            .into_response());
+    }
+
+    let png = match state.og_images.cached(&share_id, revision) {
+        Some(png) => png,
+        None => {
+            let renderer = state.og_images.clone();
+            let id = share_id.clone();
+            let items = match share.data {
+                Some(serde_json::Value::Array(items)) => items,
+                _ => Vec::new(),
+            };
+
+            // Rasterizing takes tens of milliseconds; keep it off the async workers
+            let rendered = tokio::task::spawn_blocking(move || {
+                renderer.render(&id, revision, &Transcript::from_items(&items))
+            })
+            .await;
+
+            match rendered {
+                Ok(Ok(png)) => {
+                    info!("🖼️ Preview image rendered - ID: {}", share_id);
+                    png
+                }
+                Ok(Err(e)) => {
+                    error!(
+                        "❌ Failed to render preview image - ID: {} - Error: {}",
+                        share_id, e
+                    );
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                Err(e) => {
+                    error!(
+                        "❌ Preview image task failed - ID: {} - Error: {}",
+                        share_id, e
+                    );
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            }
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png".to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        png,
+    )
+        .into_response())
+}
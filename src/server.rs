@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Context, Result};
+use axum::{body::Body, extract::ConnectInfo, extract::Request, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::os::fd::{FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::net::{TcpListener, UnixListener};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use tower::ServiceExt;
+use tracing::{debug, error, info, warn};
+
+use crate::settings::ServerSettings;
+
+/// First file descriptor passed by systemd socket activation (`SD_LISTEN_FDS_START`).
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// Peer address reported for connections accepted on a Unix socket, so that a local
+/// reverse proxy can be trusted by listing `127.0.0.1` in `server.trusted_proxies`.
+const UNIX_PEER_ADDR: SocketAddr = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+
+/// Where the server accepts connections.
+pub enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener, Option<PathBuf>),
+}
+
+impl Listener {
+    /// Bind according to settings, preferring in order: a socket passed by systemd
+    /// (`LISTEN_FDS`), `server.unix_socket`, then `server.host`:`server.port`.
+    pub async fn bind(settings: &ServerSettings) -> Result<Self> {
+        if let Some(fd) = systemd_listen_fd()? {
+            return Self::from_fd(fd);
+        }
+
+        if let Some(path) = &settings.unix_socket {
+            return Self::bind_unix(path);
+        }
+
+        let addr = settings.bind_address();
+        let listener = TcpListener::bind(&addr)
+            .await
+            .with_context(|| format!("Failed to bind {}", addr))?;
+        Ok(Self::Tcp(listener))
+    }
+
+    /// Bind a Unix domain socket, replacing a stale socket file left by a previous run.
+    pub fn bind_unix(path: &Path) -> Result<Self> {
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(anyhow!(
+                    "Refusing to replace non-socket file at {}",
+                    path.display()
+                ));
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind Unix socket {}", path.display()))?;
+        Ok(Self::Unix(listener, Some(path.to_path_buf())))
+    }
+
+    /// Adopt an inherited listening socket, detecting whether it is TCP or Unix.
+    fn from_fd(fd: RawFd) -> Result<Self> {
+        // SAFETY: the fd was handed to this process by systemd for exclusive use and is
+        // only adopted once; ownership is released again if it is not a Unix socket.
+        let unix = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+        if unix.local_addr().is_ok() {
+            unix.set_nonblocking(true)?;
+            return Ok(Self::Unix(UnixListener::from_std(unix)?, None));
+        }
+        let fd = unix.into_raw_fd();
+
+        // SAFETY: as above; the fd is not owned by anything else at this point.
+        let tcp = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+        tcp.local_addr()
+            .context("Inherited LISTEN_FDS socket is neither TCP nor Unix")?;
+        tcp.set_nonblocking(true)?;
+        Ok(Self::Tcp(TcpListener::from_std(tcp)?))
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Tcp(listener) => listener
+                .local_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_else(|_| "tcp (unknown address)".to_string()),
+            Self::Unix(listener, _) => listener
+                .local_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(|p| format!("unix:{}", p.display())))
+                .unwrap_or_else(|| "unix (inherited)".to_string()),
+        }
+    }
+}
+
+/// Serve the application until `shutdown` resolves, then stop accepting connections and
+/// wait up to `drain_timeout` for in-flight requests to finish.
+pub async fn serve<F>(
+    listener: Listener,
+    app: Router,
+    shutdown: F,
+    drain_timeout: Duration,
+) -> Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = watch::channel(false);
+    tokio::spawn(async move {
+        shutdown.await;
+        let _ = tx.send(true);
+    });
+
+    let server = async {
+        match listener {
+            Listener::Tcp(listener) => {
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown_requested(rx.clone()))
+                .await?;
+                Ok(())
+            }
+            Listener::Unix(listener, path) => {
+                serve_unix(listener, path, app, shutdown_requested(rx.clone())).await
+            }
+        }
+    };
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => result,
+        _ = async {
+            shutdown_requested(rx.clone()).await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            warn!(
+                "Drain timeout of {:?} elapsed, closing remaining connections",
+                drain_timeout
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Resolves on SIGINT (Ctrl+C) or SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    let terminate = async {
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Resolves once shutdown has been requested; never resolves if the sender is gone without it.
+async fn shutdown_requested(mut rx: watch::Receiver<bool>) {
+    if rx.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn serve_unix(
+    listener: UnixListener,
+    path: Option<PathBuf>,
+    app: Router,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => accepted?.0,
+            _ = &mut shutdown => break,
+        };
+
+        let service = app.clone().map_request(|request: Request<Incoming>| {
+            let mut request = request.map(Body::new);
+            request.extensions_mut().insert(ConnectInfo(UNIX_PEER_ADDR));
+            request
+        });
+        let connection = hyper::server::conn::http1::Builder::new()
+            .serve_connection(TokioIo::new(stream), TowerToHyperService::new(service));
+        let connection = graceful.watch(connection);
+
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("Unix socket connection error: {}", e);
+            }
+        });
+    }
+
+    // Stop accepting before draining so a restarted instance can bind the path
+    drop(listener);
+    if let Some(path) = path {
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove socket {}: {}", path.display(), e);
+        }
+    }
+
+    graceful.shutdown().await;
+    Ok(())
+}
+
+/// The first socket passed by systemd, if this process was socket-activated.
+fn systemd_listen_fd() -> Result<Option<RawFd>> {
+    let pid = match std::env::var("LISTEN_PID") {
+        Ok(pid) => pid,
+        Err(_) => return Ok(None),
+    };
+    if pid.parse::<u32>().ok() != Some(std::process::id()) {
+        return Ok(None);
+    }
+
+    let fds: RawFd = std::env::var("LISTEN_FDS")
+        .context("LISTEN_PID set without LISTEN_FDS")?
+        .parse()
+        .context("Invalid LISTEN_FDS")?;
+    if fds < 1 {
+        return Ok(None);
+    }
+    if fds > 1 {
+        warn!("systemd passed {} sockets, only the first is used", fds);
+    }
+
+    info!("Using socket passed by systemd (LISTEN_FDS={})", fds);
+    Ok(Some(SD_LISTEN_FDS_START))
+}
@@ -0,0 +1,291 @@
+use anyhow::{Context, Result};
+use config::{Config, Environment, File, FileFormat};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::core::analytics::AnalyticsConfig;
+use crate::core::limits::Limits;
+use crate::core::redact::RedactionConfig;
+
+/// Config file read when `CONFIG_FILE` is not set. Missing files are ignored.
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Prefix for environment overrides, e.g. `OPENCODE_SHARE_DATABASE__MAX_CONNECTIONS=20`.
+const ENV_PREFIX: &str = "OPENCODE_SHARE";
+
+/// Keys parsed as comma-separated lists when set from the environment.
+const LIST_KEYS: &[&str] = &[
+    "server.allowed_hosts",
+    "server.trusted_proxies",
+    "cors.allowed_origins",
+    "embed.frame_ancestors",
+];
+
+/// Plain environment variable names kept for compatibility, mapped to their setting keys.
+const LEGACY_ENV_VARS: &[(&str, &str)] = &[
+    ("HOST", "server.host"),
+    ("PORT", "server.port"),
+    ("PUBLIC_BASE_URL", "server.public_base_url"),
+    ("ALLOWED_HOSTS", "server.allowed_hosts"),
+    ("TRUSTED_PROXIES", "server.trusted_proxies"),
+    ("UNIX_SOCKET", "server.unix_socket"),
+    ("DRAIN_TIMEOUT_SECS", "server.drain_timeout_secs"),
+    ("DATABASE_URL", "database.url"),
+    ("MAX_BODY_BYTES", "limits.max_body_bytes"),
+    ("MAX_SYNC_ITEMS", "limits.max_sync_items"),
+    ("MAX_ITEM_BYTES", "limits.max_item_bytes"),
+    ("MAX_SHARE_BYTES", "limits.max_share_bytes"),
+    ("REDACTION_ENABLED", "redaction.enabled"),
+    ("REDACTION_RULES_FILE", "redaction.rules_file"),
+    ("REDACTION_ENTROPY_THRESHOLD", "redaction.entropy_threshold"),
+    ("TEMPLATES_OVERRIDE_DIR", "paths.templates_override_dir"),
+    ("TEMPLATES_RELOAD", "paths.templates_reload"),
+    ("LOG_FORMAT", "logging.format"),
+    ("OTEL_EXPORTER_OTLP_ENDPOINT", "telemetry.otlp_endpoint"),
+    ("OTEL_SERVICE_NAME", "telemetry.service_name"),
+    ("ANALYTICS_ENABLED", "analytics.enabled"),
+    ("ANALYTICS_IP_HASH_SALT", "analytics.ip_hash_salt"),
+    ("EMBED_FRAME_ANCESTORS", "embed.frame_ancestors"),
+    ("ADMIN_TOKEN", "admin.token"),
+];
+
+/// Application settings.
+///
+/// Loaded in order of increasing precedence from built-in defaults, a TOML file
+/// (`CONFIG_FILE`, default `config.toml`), the plain environment variables listed in
+/// `LEGACY_ENV_VARS`, and `OPENCODE_SHARE_<SECTION>__<KEY>` environment variables.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub database: DatabaseSettings,
+    pub limits: Limits,
+    pub redaction: RedactionConfig,
+    pub cors: CorsSettings,
+    pub paths: PathSettings,
+    pub logging: LoggingSettings,
+    pub telemetry: TelemetrySettings,
+    pub analytics: AnalyticsConfig,
+    pub embed: EmbedSettings,
+    pub admin: AdminSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    pub host: String,
+    pub port: u16,
+    /// Always used as the base of generated share URLs when set
+    pub public_base_url: Option<String>,
+    /// Hosts accepted from `Host`/`X-Forwarded-Host` when no base URL is set
+    pub allowed_hosts: Vec<String>,
+    /// Reverse proxies whose forwarded client IP headers are trusted
+    pub trusted_proxies: Vec<String>,
+    /// Listen on this Unix domain socket instead of `host`:`port`
+    pub unix_socket: Option<PathBuf>,
+    /// Seconds to wait for in-flight requests after SIGTERM/SIGINT before exiting
+    pub drain_timeout_secs: u64,
+}
+
+impl ServerSettings {
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_secs)
+    }
+
+    pub fn bind_address(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3006,
+            public_base_url: None,
+            allowed_hosts: Vec::new(),
+            trusted_proxies: Vec::new(),
+            unix_socket: None,
+            drain_timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseSettings {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            url: "postgres://postgres@localhost/opencode_share".to_string(),
+            max_connections: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CorsSettings {
+    /// Allowed origins; empty or `*` allows any origin
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PathSettings {
+    pub templates_dir: PathBuf,
+    /// Templates here take precedence over `templates_dir`, for operator branding
+    pub templates_override_dir: Option<PathBuf>,
+    /// Re-read templates on every render; defaults to on in debug builds
+    pub templates_reload: bool,
+    pub static_dir: PathBuf,
+}
+
+impl Default for PathSettings {
+    fn default() -> Self {
+        Self {
+            templates_dir: PathBuf::from("templates"),
+            templates_override_dir: None,
+            templates_reload: cfg!(debug_assertions),
+            static_dir: PathBuf::from("static"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EmbedSettings {
+    /// Sources allowed to frame `/share/:id/embed`, sent as CSP `frame-ancestors`
+    pub frame_ancestors: Vec<String>,
+    /// Iframe size suggested by `/oembed`, before `maxwidth`/`maxheight` are applied
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for EmbedSettings {
+    fn default() -> Self {
+        Self {
+            frame_ancestors: vec!["*".to_string()],
+            width: 800,
+            height: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AdminSettings {
+    /// Bearer token for `/admin/api`; the admin API is disabled while unset
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    /// `text` for human-readable lines, `json` for one JSON object per event
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    /// OTLP/HTTP collector base URL, e.g. `http://localhost:4318`; unset disables export
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+    /// Fraction of new traces sampled; incoming `traceparent` sampling decisions are kept
+    pub sample_ratio: f64,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: "opencode-share".to_string(),
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    /// Load settings from `CONFIG_FILE` (or `config.toml`) and the process environment.
+    pub fn load() -> Result<Self> {
+        let path = env::var("CONFIG_FILE").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_string());
+        Self::load_from(Some(Path::new(&path)), env::vars().collect())
+    }
+
+    /// Load settings from an optional TOML file and the given environment variables.
+    ///
+    /// A missing file is only an error if it is not the default config file.
+    pub fn load_from(path: Option<&Path>, mut env_vars: HashMap<String, String>) -> Result<Self> {
+        let mut builder = Config::builder();
+
+        if let Some(path) = path {
+            let required = path != Path::new(DEFAULT_CONFIG_FILE);
+            builder = builder.add_source(
+                File::from(path)
+                    .format(FileFormat::Toml)
+                    .required(required),
+            );
+        }
+
+        // Legacy variables apply unless the prefixed form is also set
+        for (var, key) in LEGACY_ENV_VARS {
+            if let Some(value) = env_vars.get(*var).cloned() {
+                env_vars.entry(prefixed_env_var(key)).or_insert(value);
+            }
+        }
+
+        // Allow spaces around list separators, e.g. `127.0.0.1, 10.0.0.0/8`
+        for key in LIST_KEYS {
+            if let Some(value) = env_vars.get_mut(&prefixed_env_var(key)) {
+                *value = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(",");
+            }
+        }
+
+        let mut environment = Environment::with_prefix(ENV_PREFIX)
+            .prefix_separator("_")
+            .separator("__")
+            .list_separator(",")
+            .try_parsing(true)
+            .source(Some(env_vars.into_iter().collect()));
+        for key in LIST_KEYS {
+            environment = environment.with_list_parse_key(key);
+        }
+
+        builder
+            .add_source(environment)
+            .build()
+            .context("Failed to load configuration")?
+            .try_deserialize()
+            .context("Invalid configuration")
+    }
+}
+
+/// `server.port` -> `OPENCODE_SHARE_SERVER__PORT`
+fn prefixed_env_var(key: &str) -> String {
+    format!("{}_{}", ENV_PREFIX, key.replace('.', "__").to_uppercase())
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use axum::http::HeaderMap;
+use tracing::Span;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::settings::{LogFormat, LoggingSettings, TelemetrySettings};
+
+#[cfg(feature = "otel")]
+use opentelemetry::{
+    propagation::{Extractor, TextMapPropagator},
+    trace::TracerProvider as _,
+    KeyValue,
+};
+#[cfg(feature = "otel")]
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{Sampler, SdkTracerProvider},
+    Resource,
+};
+#[cfg(feature = "otel")]
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Log filter used when `RUST_LOG` is not set.
+const DEFAULT_FILTER: &str = "opencode_share=debug,tower_http=debug,access_log=info";
+
+/// Path appended to `telemetry.otlp_endpoint`, as for `OTEL_EXPORTER_OTLP_ENDPOINT`.
+#[cfg(feature = "otel")]
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+/// The installed tracing pipeline. Call `shutdown` before exit to flush exported spans.
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    /// Install the global subscriber: log output in the configured format plus, when an
+    /// OTLP endpoint is configured, span export.
+    pub fn init(logging: &LoggingSettings, settings: &TelemetrySettings) -> Result<Self> {
+        let env_filter =
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_FILTER.into());
+        let fmt_layer = match logging.format {
+            LogFormat::Text => fmt::layer().boxed(),
+            LogFormat::Json => fmt::layer().json().flatten_event(true).boxed(),
+        };
+
+        #[cfg(feature = "otel")]
+        {
+            let provider = tracer_provider(settings)?;
+            let otel_layer = provider.as_ref().map(|provider| {
+                tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+            });
+
+            tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(otel_layer)
+                .with(env_filter)
+                .try_init()?;
+
+            if let Some(endpoint) = &settings.otlp_endpoint {
+                tracing::info!("Exporting traces to {}", endpoint);
+            }
+
+            Ok(Self { provider })
+        }
+
+        #[cfg(not(feature = "otel"))]
+        {
+            tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(env_filter)
+                .try_init()?;
+
+            if settings.otlp_endpoint.is_some() {
+                tracing::warn!("telemetry.otlp_endpoint is set but the `otel` feature is disabled");
+            }
+
+            Ok(Self {})
+        }
+    }
+
+    /// Flush pending spans and stop the exporter.
+    pub fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("Failed to flush traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Build an OTLP/HTTP tracer provider, or `None` when no endpoint is configured.
+#[cfg(feature = "otel")]
+pub fn tracer_provider(settings: &TelemetrySettings) -> Result<Option<SdkTracerProvider>> {
+    let endpoint = match settings.otlp_endpoint.as_deref().map(str::trim) {
+        Some(endpoint) if !endpoint.is_empty() => endpoint,
+        _ => return Ok(None),
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_protocol(Protocol::HttpBinary)
+        .with_endpoint(format!(
+            "{}{}",
+            endpoint.trim_end_matches('/'),
+            OTLP_TRACES_PATH
+        ))
+        .build()?;
+
+    let resource = Resource::builder()
+        .with_service_name(settings.service_name.clone())
+        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            settings.sample_ratio,
+        ))))
+        .with_resource(resource)
+        .build();
+
+    Ok(Some(provider))
+}
+
+/// Continue the trace from an incoming W3C `traceparent` header, if any.
+pub fn set_remote_parent(span: &Span, headers: &HeaderMap) {
+    #[cfg(feature = "otel")]
+    {
+        let context = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+        // Fails only when no OpenTelemetry layer is installed
+        let _ = span.set_parent(context);
+    }
+
+    #[cfg(not(feature = "otel"))]
+    let _ = (span, headers);
+}
+
+#[cfg(feature = "otel")]
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+#[cfg(feature = "otel")]
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
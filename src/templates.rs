@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use minijinja::{path_loader, Environment};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use crate::settings::PathSettings;
+
+/// Template for the share page.
+pub const SHARE_TEMPLATE: &str = "share.html";
+
+/// Template for the embeddable share view.
+pub const EMBED_TEMPLATE: &str = "embed.html";
+
+/// Template for standalone HTML exports with styles, script and data inlined.
+pub const EXPORT_TEMPLATE: &str = "export.html";
+
+/// Templates loaded at startup.
+const TEMPLATES: &[&str] = &[SHARE_TEMPLATE, EMBED_TEMPLATE, EXPORT_TEMPLATE];
+
+/// HTML templates, looked up in the override directory first and then `templates_dir`.
+///
+/// Templates are compiled once and cached unless `paths.templates_reload` is set, in which
+/// case they are re-read from disk on every render so edits show up without a restart.
+/// Values are HTML-escaped in `.html` templates unless marked `|safe`.
+pub struct Templates {
+    env: Environment<'static>,
+    dirs: Vec<PathBuf>,
+    reload: bool,
+}
+
+impl Templates {
+    pub fn new(paths: &PathSettings) -> Result<Self> {
+        let dirs: Vec<PathBuf> = paths
+            .templates_override_dir
+            .iter()
+            .chain([&paths.templates_dir])
+            .cloned()
+            .collect();
+        let env = environment(&dirs);
+
+        // Fail at startup rather than on the first request when a template is broken
+        if !paths.templates_reload {
+            for name in TEMPLATES {
+                env.get_template(name)
+                    .with_context(|| format!("Failed to load template {}", name))?;
+            }
+        }
+
+        Ok(Self {
+            env,
+            dirs,
+            reload: paths.templates_reload,
+        })
+    }
+
+    pub fn render<S: Serialize>(&self, name: &str, context: S) -> Result<String> {
+        let env = if self.reload {
+            Cow::Owned(environment(&self.dirs))
+        } else {
+            Cow::Borrowed(&self.env)
+        };
+
+        let html = env
+            .get_template(name)
+            .and_then(|template| template.render(context))
+            .with_context(|| format!("Failed to render template {}", name))?;
+
+        Ok(html)
+    }
+}
+
+fn environment(dirs: &[PathBuf]) -> Environment<'static> {
+    let loaders: Vec<_> = dirs.iter().map(path_loader).collect();
+
+    let mut env = Environment::new();
+    env.set_loader(move |name| {
+        for loader in &loaders {
+            if let Some(source) = loader(name)? {
+                return Ok(Some(source));
+            }
+        }
+        Ok(None)
+    });
+    env
+}
@@ -0,0 +1,188 @@
+// Integration tests for request IDs and structured access logs
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    middleware::from_fn_with_state,
+    Router,
+};
+use opencode_share::middleware::{access_log_middleware, REQUEST_ID_HEADER};
+use opencode_share::settings::{LogFormat, LoggingSettings, Settings};
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogBuffer {
+    fn lines(&self) -> Vec<Value> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("Log line is not JSON"))
+            .collect()
+    }
+}
+
+async fn get_test_app(format: LogFormat) -> Router {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    sqlx::query("DELETE FROM shares WHERE session_id LIKE 'test-session-access-log%'")
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let settings = Settings {
+        logging: LoggingSettings { format },
+        ..Settings::default()
+    };
+    let state = AppState::new(pool, settings).expect("Failed to build app state");
+    Router::new()
+        .nest("/api", opencode_share::routes::api_routes())
+        .layer(from_fn_with_state(state.clone(), access_log_middleware))
+        .with_state(state)
+}
+
+fn post(uri: &str) -> axum::http::request::Builder {
+    Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, "access-log-test")
+}
+
+async fn create_share(app: &Router, session_id: &str) -> Value {
+    let body = json!({ "sessionID": session_id });
+    let request = post("/api/share")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_request_id_is_generated() {
+    let app = get_test_app(LogFormat::Text).await;
+
+    let request = Request::builder()
+        .uri("/api/share/missing/data")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(request_id).is_ok());
+}
+
+#[tokio::test]
+async fn test_request_id_is_propagated() {
+    let app = get_test_app(LogFormat::Text).await;
+
+    let request = Request::builder()
+        .uri("/api/share/missing/data")
+        .header(REQUEST_ID_HEADER, "upstream-request-42")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "upstream-request-42");
+}
+
+#[tokio::test]
+async fn test_invalid_request_id_is_replaced() {
+    let app = get_test_app(LogFormat::Text).await;
+
+    let request = Request::builder()
+        .uri("/api/share/missing/data")
+        .header(REQUEST_ID_HEADER, "x".repeat(200))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(request_id).is_ok());
+}
+
+#[tokio::test]
+async fn test_json_access_log_fields() {
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .flatten_event(true)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = get_test_app(LogFormat::Json).await;
+    let share = create_share(&app, "test-session-access-log").await;
+    let share_id = share["id"].as_str().unwrap();
+
+    let body = json!({
+        "secret": share["secret"],
+        "data": [{ "_key": "session", "title": "Logged" }]
+    });
+    let request = post(&format!("/api/share/{}/sync", share_id))
+        .header(REQUEST_ID_HEADER, "sync-request-1")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let lines = logs.lines();
+    let access = lines
+        .iter()
+        .find(|line| line["target"] == "access_log" && line["request_id"] == "sync-request-1")
+        .expect("No access log line for the sync request");
+
+    assert_eq!(access["level"], "INFO");
+    assert_eq!(access["method"], "POST");
+    assert_eq!(access["route"], "/api/share/:share_id/sync");
+    assert_eq!(access["share_id"], share_id);
+    assert_eq!(access["status"], 200);
+    assert!(access["latency_ms"].is_number());
+    assert_eq!(access["bytes"], 0);
+    assert_eq!(access["ip"], "Unknown");
+    assert_eq!(access["user_agent"], "access-log-test");
+
+    // Events logged while handling the request carry its ID through the request span
+    let handler = lines
+        .iter()
+        .find(|line| {
+            line["message"]
+                .as_str()
+                .is_some_and(|m| m.contains("Successfully synced"))
+        })
+        .expect("No sync log line");
+    assert_eq!(handler["span"]["request_id"], "sync-request-1");
+}
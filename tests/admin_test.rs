@@ -0,0 +1,213 @@
+// Tests for the admin share listing, deletion and storage endpoints
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    middleware::from_fn_with_state,
+    Router,
+};
+use opencode_share::core::share::ShareService;
+use opencode_share::middleware::admin_auth_middleware;
+use opencode_share::routes::admin_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+const TOKEN: &str = "admin-test-token";
+
+async fn get_test_pool() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+async fn delete_share(pool: &PgPool, share_id: &str) {
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(pool)
+        .await
+        .expect("Failed to clean test database");
+}
+
+/// Create a share with a session title, then pin its creation day and size.
+async fn create_share(pool: &PgPool, share_id: &str, created: &str, storage_bytes: i64) {
+    delete_share(pool, share_id).await;
+    let service = ShareService::new(pool.clone());
+    let share = service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    service
+        .sync(
+            share_id,
+            &share.secret,
+            vec![json!({ "_key": "session", "type": "session", "data": { "id": share_id, "title": format!("Title of {}", share_id) } })],
+        )
+        .await
+        .expect("Failed to sync share");
+    sqlx::query("UPDATE shares SET created_at = $2::DATE, storage_bytes = $3 WHERE id = $1")
+        .bind(share_id)
+        .bind(created)
+        .bind(storage_bytes)
+        .execute(pool)
+        .await
+        .expect("Failed to update share");
+}
+
+fn admin_app(pool: PgPool) -> Router {
+    let mut settings = Settings::default();
+    settings.admin.token = Some(TOKEN.to_string());
+    let state = AppState::new(pool, settings).expect("Failed to build app state");
+    Router::new()
+        .nest(
+            "/admin/api",
+            admin_routes().layer(from_fn_with_state(state.clone(), admin_auth_middleware)),
+        )
+        .with_state(state)
+}
+
+async fn call(app: &Router, method: Method, uri: &str) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// IDs of the listed shares that belong to this test, in listing order.
+fn listed(page: &Value, ids: &[&str]) -> Vec<String> {
+    page["shares"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|share| share["id"].as_str())
+        .filter(|id| ids.contains(id))
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_shares() {
+    let ids = ["test-admin-list-a", "test-admin-list-b", "test-admin-list-c"];
+    let pool = get_test_pool().await;
+    create_share(&pool, ids[0], "2020-01-03", 300).await;
+    create_share(&pool, ids[1], "2020-01-01", 100).await;
+    create_share(&pool, ids[2], "2020-01-02", 200).await;
+    let app = admin_app(pool.clone());
+
+    let (status, page) = call(&app, Method::GET, "/admin/api/shares?sort=size&order=asc&limit=500").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed(&page, &ids), [ids[1], ids[2], ids[0]]);
+    assert!(page["total"].as_i64().unwrap() >= 3);
+    assert_eq!(page["limit"], 500);
+
+    let (_, page) = call(&app, Method::GET, "/admin/api/shares?sort=created&limit=500").await;
+    assert_eq!(listed(&page, &ids), [ids[0], ids[2], ids[1]]);
+
+    let (_, page) = call(&app, Method::GET, "/admin/api/shares?limit=100000&offset=-5").await;
+    assert_eq!(page["limit"], 500);
+    assert_eq!(page["offset"], 0);
+
+    let (_, page) = call(&app, Method::GET, "/admin/api/shares?limit=1").await;
+    assert_eq!(page["shares"].as_array().unwrap().len(), 1);
+
+    let (status, _) = call(&app, Method::GET, "/admin/api/shares?sort=title").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for id in ids {
+        delete_share(&pool, id).await;
+    }
+}
+
+#[tokio::test]
+async fn test_lookup_by_session_id() {
+    let share_id = "test-admin-lookup";
+    let pool = get_test_pool().await;
+    create_share(&pool, share_id, "2021-06-01", 42).await;
+    let app = admin_app(pool.clone());
+
+    let (status, page) = call(
+        &app,
+        Method::GET,
+        &format!("/admin/api/shares?session_id={}", share_id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["total"], 1);
+    let share = &page["shares"][0];
+    assert_eq!(share["id"], share_id);
+    assert_eq!(share["session_id"], share_id);
+    assert_eq!(share["title"], format!("Title of {}", share_id));
+    assert_eq!(share["storage_bytes"], 42);
+    assert!(share.get("secret").is_none());
+    assert!(share.get("data").is_none());
+
+    let (_, page) = call(&app, Method::GET, "/admin/api/shares?session_id=test-admin-missing").await;
+    assert_eq!(page, json!({ "shares": [], "total": 0, "limit": 50, "offset": 0 }));
+
+    delete_share(&pool, share_id).await;
+}
+
+#[tokio::test]
+async fn test_force_delete() {
+    let share_id = "test-admin-delete";
+    let pool = get_test_pool().await;
+    create_share(&pool, share_id, "2021-06-01", 10).await;
+    let app = admin_app(pool.clone());
+
+    let uri = format!("/admin/api/shares/{}", share_id);
+    let (status, _) = call(&app, Method::DELETE, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(ShareService::new(pool).get(share_id).await.unwrap().is_none());
+
+    let (status, _) = call(&app, Method::DELETE, &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_storage_stats() {
+    let share_id = "test-admin-stats";
+    let pool = get_test_pool().await;
+    create_share(&pool, share_id, "2021-06-01", 1_000_000).await;
+    let app = admin_app(pool.clone());
+
+    let (status, totals) = call(&app, Method::GET, "/admin/api/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(totals["shares"].as_i64().unwrap() >= 1);
+    assert!(totals["storage_bytes"].as_i64().unwrap() >= 1_000_000);
+    assert!(totals["largest_share_bytes"].as_i64().unwrap() >= 1_000_000);
+    assert!(totals["empty_shares"].as_i64().is_some());
+    assert!(totals["views"].as_i64().is_some());
+    assert!(totals["database_bytes"].as_i64().unwrap() > 0);
+
+    // The admin token is required
+    let response = app
+        .oneshot(Request::get("/admin/api/stats").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    delete_share(&pool, share_id).await;
+}
@@ -0,0 +1,245 @@
+// Tests for share view recording and the stats endpoint
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    middleware::from_fn_with_state,
+    Router,
+};
+use opencode_share::core::analytics::{classify_user_agent, normalize_referrer};
+use opencode_share::routes::{api_routes, share_routes};
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn get_test_state(session_id: &str) -> AppState {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(session_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    AppState::new(pool, Settings::default()).expect("Failed to build app state")
+}
+
+fn test_app(state: AppState) -> Router {
+    Router::new()
+        .nest("/api", api_routes())
+        .nest("/share", share_routes())
+        .layer(from_fn_with_state(
+            state.clone(),
+            opencode_share::middleware::access_log_middleware,
+        ))
+        .with_state(state)
+}
+
+async fn get(app: &Router, uri: &str, headers: &[(&str, &str)], peer: &str) -> (StatusCode, Value) {
+    let mut request = Request::builder().uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let mut request = request.body(Body::empty()).unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// Views are written in the background; wait until `count` have landed.
+async fn wait_for_views(state: &AppState, share_id: &str, count: i64) {
+    for _ in 0..100 {
+        let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM share_views WHERE share_id = $1")
+            .bind(share_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        if recorded >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Expected {} recorded views for {}", count, share_id);
+}
+
+#[test]
+fn test_classify_user_agent() {
+    assert_eq!(
+        classify_user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/605.1.15"),
+        "desktop"
+    );
+    assert_eq!(
+        classify_user_agent("Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148"),
+        "mobile"
+    );
+    assert_eq!(classify_user_agent("Slackbot-LinkExpanding 1.0"), "bot");
+    assert_eq!(classify_user_agent("curl/8.4.0"), "cli");
+    assert_eq!(classify_user_agent(""), "unknown");
+}
+
+#[test]
+fn test_normalize_referrer() {
+    assert_eq!(
+        normalize_referrer("https://github.com/org/repo/pull/1?token=abc#diff").as_deref(),
+        Some("https://github.com/org/repo/pull/1")
+    );
+    assert_eq!(normalize_referrer("android-app://com.slack"), None);
+    assert_eq!(
+        normalize_referrer(&format!("https://example.com/{}", "a".repeat(1000)))
+            .unwrap()
+            .len(),
+        512
+    );
+}
+
+#[tokio::test]
+async fn test_share_views_are_recorded_and_reported() {
+    let share_id = "test-session-analytics";
+    let state = get_test_state(share_id).await;
+    let share = state
+        .share_service()
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    let app = test_app(state.clone());
+
+    let desktop = "Mozilla/5.0 (X11; Linux x86_64) Gecko/20100101 Firefox/120.0";
+    let (status, _) = get(
+        &app,
+        &format!("/share/{}", share_id),
+        &[
+            ("user-agent", desktop),
+            ("referer", "https://github.com/org/repo/pull/1?token=abc"),
+        ],
+        "203.0.113.7:50000",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for peer in ["198.51.100.2:40000", "198.51.100.2:40001"] {
+        let (status, _) = get(
+            &app,
+            &format!("/api/share/{}/data", share_id),
+            &[("user-agent", "curl/8.4.0")],
+            peer,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    wait_for_views(&state, share_id, 3).await;
+
+    // Raw IPs are never stored
+    let stored: Vec<String> = sqlx::query_scalar("SELECT ip_hash FROM share_views WHERE share_id = $1")
+        .bind(share_id)
+        .fetch_all(&state.db)
+        .await
+        .unwrap();
+    assert!(stored.iter().all(|hash| hash.len() == 64 && !hash.contains('.')));
+
+    let (status, stats) = get(
+        &app,
+        &format!("/api/share/{}/stats?days=7", share_id),
+        &[("x-share-secret", &share.secret)],
+        "203.0.113.7:50000",
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["total_views"], 3);
+    assert_eq!(stats["unique_visitors"], 2);
+    assert_eq!(stats["page_views"], 1);
+    assert_eq!(stats["data_fetches"], 2);
+    assert_eq!(stats["daily"].as_array().unwrap().len(), 1);
+    assert_eq!(stats["daily"][0]["views"], 3);
+    assert_eq!(stats["daily"][0]["unique_visitors"], 2);
+    assert_eq!(stats["user_agents"], json!({ "cli": 2, "desktop": 1 }));
+    assert_eq!(
+        stats["referrers"],
+        json!([{ "referrer": "https://github.com/org/repo/pull/1", "views": 1 }])
+    );
+}
+
+#[tokio::test]
+async fn test_share_stats_requires_secret() {
+    let share_id = "test-session-analytics-secret";
+    let state = get_test_state(share_id).await;
+    state
+        .share_service()
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    let app = test_app(state);
+
+    let (status, _) = get(
+        &app,
+        &format!("/api/share/{}/stats", share_id),
+        &[("x-share-secret", "wrong-secret")],
+        "203.0.113.7:50000",
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = get(
+        &app,
+        "/api/share/test-session-analytics-missing/stats",
+        &[("x-share-secret", "wrong-secret")],
+        "203.0.113.7:50000",
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_share_views_removed_with_share() {
+    let share_id = "test-session-analytics-remove";
+    let state = get_test_state(share_id).await;
+    let share = state
+        .share_service()
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+
+    state
+        .view_service()
+        .record(share_id, opencode_share::core::analytics::ViewKind::Page, "203.0.113.7", None, None)
+        .await
+        .expect("Failed to record view");
+    state
+        .share_service()
+        .remove(share_id, &share.secret)
+        .await
+        .expect("Failed to remove share");
+
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM share_views WHERE share_id = $1")
+        .bind(share_id)
+        .fetch_one(&state.db)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
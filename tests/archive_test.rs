@@ -0,0 +1,304 @@
+// Tests for backup archives and the admin archive endpoints
+
+use async_compression::tokio::{bufread::GzipDecoder, write::GzipEncoder};
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    middleware::from_fn_with_state,
+    Router,
+};
+use chrono::{Duration, Utc};
+use opencode_share::core::archive::{
+    export_archive, import_archive, ArchiveHeader, ArchivedShare, ImportSummary, InvalidArchive,
+    ARCHIVE_FORMAT, ARCHIVE_VERSION,
+};
+use opencode_share::core::share::ShareService;
+use opencode_share::middleware::admin_auth_middleware;
+use opencode_share::routes::admin_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tower::ServiceExt;
+
+async fn get_test_pool() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+async fn delete_share(pool: &PgPool, share_id: &str) {
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(pool)
+        .await
+        .expect("Failed to clean test database");
+}
+
+async fn create_share(pool: &PgPool, share_id: &str) -> ArchivedShare {
+    let service = ShareService::new(pool.clone());
+    let share = service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    service
+        .sync(
+            share_id,
+            &share.secret,
+            vec![json!({ "_key": "session", "type": "session", "data": { "id": share_id, "title": "Archived" } })],
+        )
+        .await
+        .expect("Failed to sync share");
+    sqlx::query(
+        "INSERT INTO share_views (share_id, kind, ip_hash, user_agent_class, referrer) VALUES ($1, 'page', 'abc', 'browser', 'github.com')",
+    )
+    .bind(share_id)
+    .execute(pool)
+    .await
+    .expect("Failed to record view");
+
+    archived_copy(pool, share_id).await
+}
+
+/// The archived form of one share, taken from a full export.
+async fn archived_copy(pool: &PgPool, share_id: &str) -> ArchivedShare {
+    let mut archive = Vec::new();
+    export_archive(pool, &mut archive)
+        .await
+        .expect("Failed to export archive");
+    let lines = decompress(&archive).await;
+    let header: ArchiveHeader = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+    assert_eq!(header.format, ARCHIVE_FORMAT);
+    assert_eq!(header.version, ARCHIVE_VERSION);
+
+    lines
+        .lines()
+        .skip(1)
+        .map(|line| serde_json::from_str::<ArchivedShare>(line).unwrap())
+        .find(|archived| archived.share.id == share_id)
+        .expect("Share missing from archive")
+}
+
+async fn decompress(archive: &[u8]) -> String {
+    let mut text = String::new();
+    GzipDecoder::new(archive)
+        .read_to_string(&mut text)
+        .await
+        .expect("Archive is not gzip");
+    text
+}
+
+async fn archive_of(shares: &[ArchivedShare]) -> Vec<u8> {
+    let header = ArchiveHeader {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+    };
+    let mut text = serde_json::to_string(&header).unwrap() + "\n";
+    for share in shares {
+        text += &(serde_json::to_string(share).unwrap() + "\n");
+    }
+
+    let mut out = GzipEncoder::new(Vec::new());
+    out.write_all(text.as_bytes()).await.unwrap();
+    out.shutdown().await.unwrap();
+    out.into_inner()
+}
+
+#[tokio::test]
+async fn test_archive_round_trip() {
+    let share_id = "test-archive-round-trip";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let archived = create_share(&pool, share_id).await;
+    assert_eq!(archived.views.len(), 1);
+    assert_eq!(archived.views[0].referrer.as_deref(), Some("github.com"));
+    let archive = archive_of(std::slice::from_ref(&archived)).await;
+
+    // Restoring into a database without the share recreates it with its views
+    delete_share(&pool, share_id).await;
+    let summary = import_archive(&pool, archive.as_slice()).await.unwrap();
+    assert_eq!(
+        summary,
+        ImportSummary { created: 1, updated: 0, skipped: 0, views: 1 }
+    );
+    let restored = archived_copy(&pool, share_id).await;
+    assert_eq!(restored.share.secret, archived.share.secret);
+    assert_eq!(restored.share.data, archived.share.data);
+    assert_eq!(restored.share.updated_at, archived.share.updated_at);
+    assert_eq!(restored.views.len(), 1);
+
+    // Importing the same archive again changes nothing
+    let summary = import_archive(&pool, archive.as_slice()).await.unwrap();
+    assert_eq!(
+        summary,
+        ImportSummary { created: 0, updated: 0, skipped: 1, views: 0 }
+    );
+    assert_eq!(archived_copy(&pool, share_id).await.views.len(), 1);
+
+    delete_share(&pool, share_id).await;
+}
+
+#[tokio::test]
+async fn test_archive_import_replaces_older_share() {
+    let share_id = "test-archive-newer";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let mut archived = create_share(&pool, share_id).await;
+    archived.share.data = Some(json!([{ "_key": "session", "type": "session", "data": { "title": "Newer" } }]));
+    archived.share.updated_at += Duration::minutes(5);
+    let summary = import_archive(&pool, archive_of(&[archived]).await.as_slice())
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        ImportSummary { created: 0, updated: 1, skipped: 0, views: 0 }
+    );
+
+    let data = ShareService::new(pool.clone()).get_data(share_id).await.unwrap();
+    assert_eq!(data[0]["data"]["title"], "Newer");
+    // Views of an existing share are not duplicated
+    assert_eq!(archived_copy(&pool, share_id).await.views.len(), 1);
+
+    delete_share(&pool, share_id).await;
+}
+
+#[tokio::test]
+async fn test_archive_rejects_invalid_input() {
+    let pool = get_test_pool().await;
+
+    let err = import_archive(&pool, &b"not gzip"[..]).await.unwrap_err();
+    assert!(err.downcast_ref::<InvalidArchive>().is_some());
+
+    let mut out = GzipEncoder::new(Vec::new());
+    out.write_all(b"{\"format\":\"other\",\"version\":1,\"exported_at\":\"2024-01-01T00:00:00Z\"}\n")
+        .await
+        .unwrap();
+    out.shutdown().await.unwrap();
+    let err = import_archive(&pool, out.into_inner().as_slice())
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<InvalidArchive>().is_some());
+}
+
+fn admin_app(pool: PgPool, token: Option<&str>) -> Router {
+    let mut settings = Settings::default();
+    settings.admin.token = token.map(str::to_string);
+    let state = AppState::new(pool, settings).expect("Failed to build app state");
+    Router::new()
+        .nest(
+            "/admin/api",
+            admin_routes().layer(from_fn_with_state(state.clone(), admin_auth_middleware)),
+        )
+        .with_state(state)
+}
+
+fn archive_request(method: Method, token: Option<&str>, body: Body) -> Request<Body> {
+    let mut request = Request::builder().method(method).uri("/admin/api/archive");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    request.body(body).unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_api_requires_token() {
+    let pool = get_test_pool().await;
+
+    // Without a configured token the admin API does not exist
+    let response = admin_app(pool.clone(), None)
+        .oneshot(archive_request(Method::GET, Some("anything"), Body::empty()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let app = admin_app(pool, Some("s3cret"));
+    for token in [None, Some("wrong")] {
+        let response = app
+            .clone()
+            .oneshot(archive_request(Method::GET, token, Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn test_admin_archive_endpoints() {
+    let share_id = "test-archive-admin-api";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let archived = create_share(&pool, share_id).await;
+    let app = admin_app(pool.clone(), Some("s3cret"));
+
+    let response = app
+        .clone()
+        .oneshot(archive_request(Method::GET, Some("s3cret"), Body::empty()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/gzip");
+    let disposition = response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap();
+    assert!(disposition.starts_with("attachment; filename=\"opencode-share-"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = decompress(&body).await;
+    assert!(text.lines().skip(1).any(|line| {
+        serde_json::from_str::<Value>(line).unwrap()["share"]["id"] == share_id
+    }));
+
+    delete_share(&pool, share_id).await;
+    let response = app
+        .clone()
+        .oneshot(archive_request(
+            Method::POST,
+            Some("s3cret"),
+            Body::from(archive_of(&[archived]).await),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let summary: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(summary, json!({ "created": 1, "updated": 0, "skipped": 0, "views": 1 }));
+
+    let response = app
+        .oneshot(archive_request(Method::POST, Some("s3cret"), Body::from("garbage")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    delete_share(&pool, share_id).await;
+}
+
+#[tokio::test]
+async fn test_admin_archive_download_aborts_on_failure() {
+    let pool = get_test_pool().await;
+    let app = admin_app(pool.clone(), Some("s3cret"));
+    // Every export query fails once the pool is closed
+    pool.close().await;
+
+    let response = app
+        .oneshot(archive_request(Method::GET, Some("s3cret"), Body::empty()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .is_err());
+}
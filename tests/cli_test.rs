@@ -0,0 +1,200 @@
+// Tests for the command line subcommands
+
+use clap::Parser;
+use opencode_share::cli::{run_gc, run_share, Cli, Command, ExportFormat, GcArgs, ShareCommand};
+use opencode_share::core::share::ShareService;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+
+async fn get_test_pool() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+async fn delete_share(pool: &PgPool, share_id: &str) {
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(pool)
+        .await
+        .expect("Failed to clean test database");
+}
+
+async fn create_share(pool: &PgPool, share_id: &str) {
+    let service = ShareService::new(pool.clone());
+    let share = service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    service
+        .sync(
+            share_id,
+            &share.secret,
+            vec![
+                json!({ "_key": "session", "type": "session", "data": { "id": share_id, "title": "CLI session" } }),
+                json!({ "_key": "message/msg_1", "type": "message", "data": { "id": "msg_1", "sessionID": share_id, "role": "user", "content": "Hello from the CLI" } }),
+            ],
+        )
+        .await
+        .expect("Failed to sync share");
+}
+
+async fn share(state: &AppState, command: ShareCommand) -> anyhow::Result<String> {
+    let mut out = Vec::new();
+    run_share(state, command, &mut out).await?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_parse_commands() {
+    let cli = Cli::try_parse_from(["opencode-share"]).unwrap();
+    assert!(cli.command.is_none());
+
+    let cli = Cli::try_parse_from(["opencode-share", "share", "export", "ses_1", "--format", "html"])
+        .unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::Share(ShareCommand::Export { ref id, format: ExportFormat::Html, output: None })) if id == "ses_1"
+    ));
+
+    let cli = Cli::try_parse_from(["opencode-share", "gc", "--views-older-than-days", "90"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::Gc(GcArgs { abandoned_after_hours: 24, views_older_than_days: Some(90) }))
+    ));
+
+    assert!(Cli::try_parse_from(["opencode-share", "share", "export", "ses_1", "--format", "pdf"]).is_err());
+    assert!(Cli::try_parse_from(["opencode-share", "gc", "--abandoned-after-hours", "-1"]).is_err());
+    assert!(Cli::try_parse_from(["opencode-share", "share", "list", "--limit", "-1"]).is_err());
+    assert!(Cli::try_parse_from(["opencode-share", "share", "list", "--offset", "-5"]).is_err());
+}
+
+#[tokio::test]
+async fn test_share_commands() {
+    let share_id = "test-cli-share-commands";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+    create_share(&pool, share_id).await;
+
+    let mut settings = Settings::default();
+    settings.server.public_base_url = Some("https://share.example.com".to_string());
+    let state = AppState::new(pool.clone(), settings).expect("Failed to build app state");
+
+    let list = share(&state, ShareCommand::List { limit: 1000, offset: 0, json: true })
+        .await
+        .unwrap();
+    let list: Vec<Value> = serde_json::from_str(&list).unwrap();
+    let listed = list.iter().find(|share| share["id"] == share_id).unwrap();
+    assert_eq!(listed["title"], "CLI session");
+    assert!(listed.get("data").is_none());
+
+    let shown = share(&state, ShareCommand::Show { id: share_id.to_string() })
+        .await
+        .unwrap();
+    assert!(shown.contains("Title:       CLI session"));
+    assert!(shown.contains(&format!("URL:         https://share.example.com/share/{}", share_id)));
+    assert!(shown.contains("Messages:    1"));
+
+    let export = |format| ShareCommand::Export { id: share_id.to_string(), format, output: None };
+    let markdown = share(&state, export(ExportFormat::Md)).await.unwrap();
+    assert!(markdown.starts_with("# CLI session\n"));
+    assert!(markdown.contains("Hello from the CLI"));
+    let items: Vec<Value> = serde_json::from_str(&share(&state, export(ExportFormat::Json)).await.unwrap()).unwrap();
+    assert_eq!(items.len(), 2);
+    let html = share(&state, export(ExportFormat::Html)).await.unwrap();
+    assert!(html.contains("Hello from the CLI"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("share.md");
+    let printed = share(
+        &state,
+        ShareCommand::Export { id: share_id.to_string(), format: ExportFormat::Md, output: Some(path.clone()) },
+    )
+    .await
+    .unwrap();
+    assert!(printed.is_empty());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), markdown);
+
+    let deleted = share(&state, ShareCommand::Delete { id: share_id.to_string() })
+        .await
+        .unwrap();
+    assert_eq!(deleted, format!("Deleted share {}\n", share_id));
+    assert!(state.share_service().get(share_id).await.unwrap().is_none());
+
+    let err = share(&state, ShareCommand::Delete { id: share_id.to_string() })
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), format!("Share not found: {}", share_id));
+}
+
+#[tokio::test]
+async fn test_gc_deletes_abandoned_shares() {
+    let abandoned = "test-cli-gc-abandoned";
+    let synced = "test-cli-gc-synced";
+    let pool = get_test_pool().await;
+    delete_share(&pool, abandoned).await;
+    delete_share(&pool, synced).await;
+
+    ShareService::new(pool.clone())
+        .create(abandoned.to_string())
+        .await
+        .expect("Failed to create share");
+    create_share(&pool, synced).await;
+    sqlx::query(
+        "INSERT INTO share_views (share_id, kind, viewed_at, ip_hash, user_agent_class) VALUES ($1, 'page', NOW() - INTERVAL '400 days', 'abc', 'browser')",
+    )
+    .bind(synced)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE shares SET updated_at = NOW() - INTERVAL '2 days' WHERE id = ANY($1)")
+        .bind([abandoned, synced])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let state = AppState::new(pool.clone(), Settings::default()).expect("Failed to build app state");
+    let mut out = Vec::new();
+    // A cutoff before the earliest representable time is rejected rather than deleting everything
+    assert!(run_gc(
+        &state,
+        GcArgs { abandoned_after_hours: u32::MAX, views_older_than_days: None },
+        &mut out,
+    )
+    .await
+    .is_err());
+    assert!(state.share_service().get(abandoned).await.unwrap().is_some());
+
+    run_gc(
+        &state,
+        GcArgs { abandoned_after_hours: 24, views_older_than_days: Some(365) },
+        &mut out,
+    )
+    .await
+    .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("abandoned shares"));
+    assert!(out.contains("views"));
+
+    let service = state.share_service();
+    assert!(service.get(abandoned).await.unwrap().is_none());
+    assert!(service.get(synced).await.unwrap().is_some());
+    let views: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM share_views WHERE share_id = $1")
+        .bind(synced)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(views, 0);
+
+    delete_share(&pool, synced).await;
+}
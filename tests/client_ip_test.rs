@@ -0,0 +1,133 @@
+// Tests for client IP extraction behind trusted proxies
+
+use axum::http::HeaderMap;
+use opencode_share::middleware::{get_client_ip, TrustedProxies};
+use std::net::IpAddr;
+
+fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.append(*name, value.parse().unwrap());
+    }
+    headers
+}
+
+fn ip(s: &str) -> Option<IpAddr> {
+    Some(s.parse().unwrap())
+}
+
+#[test]
+fn test_untrusted_peer_ignores_forwarded_headers() {
+    let trusted = TrustedProxies::default();
+    let headers = headers(&[
+        ("x-forwarded-for", "1.2.3.4"),
+        ("x-real-ip", "5.6.7.8"),
+        ("cf-connecting-ip", "9.9.9.9"),
+    ]);
+
+    let client_ip = get_client_ip(&headers, ip("203.0.113.7"), &trusted);
+
+    assert_eq!(client_ip, "203.0.113.7");
+}
+
+#[test]
+fn test_trusted_peer_uses_forwarded_for() {
+    let trusted = TrustedProxies::parse("10.0.0.0/8").unwrap();
+    let headers = headers(&[("x-forwarded-for", "198.51.100.20")]);
+
+    let client_ip = get_client_ip(&headers, ip("10.0.0.2"), &trusted);
+
+    assert_eq!(client_ip, "198.51.100.20");
+}
+
+#[test]
+fn test_forwarded_for_skips_only_trusted_hops() {
+    let trusted = TrustedProxies::parse("10.0.0.0/8, 127.0.0.1").unwrap();
+    // Client spoofs a leading entry; the first untrusted hop from the right wins
+    let headers = headers(&[(
+        "x-forwarded-for",
+        "6.6.6.6, 198.51.100.20, 10.1.2.3",
+    )]);
+
+    let client_ip = get_client_ip(&headers, ip("127.0.0.1"), &trusted);
+
+    assert_eq!(client_ip, "198.51.100.20");
+}
+
+#[test]
+fn test_forwarded_for_stops_at_unparsable_hop() {
+    let trusted = TrustedProxies::parse("10.0.0.0/8").unwrap();
+    // Garbage between trusted proxies must not let the spoofed entry on its left through
+    let headers = headers(&[("x-forwarded-for", "6.6.6.6, garbage, 10.1.2.3")]);
+
+    let client_ip = get_client_ip(&headers, ip("10.0.0.2"), &trusted);
+
+    assert_eq!(client_ip, "10.0.0.2");
+}
+
+#[test]
+fn test_forwarded_for_across_multiple_headers() {
+    let trusted = TrustedProxies::parse("10.0.0.0/8").unwrap();
+    let headers = headers(&[
+        ("x-forwarded-for", "198.51.100.20"),
+        ("x-forwarded-for", "10.0.0.5"),
+    ]);
+
+    let client_ip = get_client_ip(&headers, ip("10.0.0.2"), &trusted);
+
+    assert_eq!(client_ip, "198.51.100.20");
+}
+
+#[test]
+fn test_all_hops_trusted_returns_leftmost() {
+    let trusted = TrustedProxies::parse("10.0.0.0/8").unwrap();
+    let headers = headers(&[("x-forwarded-for", "10.0.0.9, 10.0.0.5")]);
+
+    let client_ip = get_client_ip(&headers, ip("10.0.0.2"), &trusted);
+
+    assert_eq!(client_ip, "10.0.0.9");
+}
+
+#[test]
+fn test_trusted_peer_falls_back_to_real_ip() {
+    let trusted = TrustedProxies::parse("::1").unwrap();
+    let headers = headers(&[("x-real-ip", "198.51.100.20")]);
+
+    let client_ip = get_client_ip(&headers, ip("::1"), &trusted);
+
+    assert_eq!(client_ip, "198.51.100.20");
+}
+
+#[test]
+fn test_trusted_peer_without_headers_returns_peer() {
+    let trusted = TrustedProxies::parse("127.0.0.1").unwrap();
+
+    let client_ip = get_client_ip(&HeaderMap::new(), ip("127.0.0.1"), &trusted);
+
+    assert_eq!(client_ip, "127.0.0.1");
+}
+
+#[test]
+fn test_ipv4_mapped_peer_matches_ipv4_network() {
+    let trusted = TrustedProxies::parse("127.0.0.1").unwrap();
+    let headers = headers(&[("x-forwarded-for", "198.51.100.20")]);
+
+    let client_ip = get_client_ip(&headers, ip("::ffff:127.0.0.1"), &trusted);
+
+    assert_eq!(client_ip, "198.51.100.20");
+}
+
+#[test]
+fn test_missing_peer_is_unknown() {
+    let trusted = TrustedProxies::parse("0.0.0.0/0").unwrap();
+    let headers = headers(&[("x-forwarded-for", "198.51.100.20")]);
+
+    let client_ip = get_client_ip(&headers, None, &trusted);
+
+    assert_eq!(client_ip, "Unknown");
+}
+
+#[test]
+fn test_invalid_trusted_proxy() {
+    assert!(TrustedProxies::parse("10.0.0.0/8, not-an-ip").is_err());
+}
@@ -0,0 +1,220 @@
+// Tests for the opencode-share-client library against the API routes
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use opencode_share::routes::api_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use opencode_share_client::models::SyncShareRequest;
+use opencode_share_client::{RetryPolicy, ShareClient};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+async fn get_test_pool() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+async fn delete_share(pool: &PgPool, share_id: &str) {
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(pool)
+        .await
+        .expect("Failed to clean test database");
+}
+
+/// Serve `app` on an ephemeral port and return its base URL.
+async fn spawn(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn fast_retry(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+    }
+}
+
+fn items(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| json!({ "_key": format!("part/{}", i), "type": "part", "data": { "text": format!("item {}", i) } }))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_client_round_trip() {
+    let share_id = "test-client-round-trip";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let mut settings = Settings::default();
+    settings.limits.max_sync_items = 2;
+    let state = AppState::new(pool, settings).expect("Failed to build app state");
+    let base_url = spawn(Router::new().nest("/api", api_routes()).with_state(state)).await;
+
+    let client = ShareClient::new(format!("{}/", base_url))
+        .with_retry(fast_retry(0))
+        .with_batch_items(2);
+    let share = client.create_share(share_id).await.unwrap();
+    assert_eq!(share.id, share_id);
+    assert!(share.url.ends_with(&format!("/share/{}", share_id)));
+
+    client
+        .sync_share(&share.id, &share.secret, &items(5))
+        .await
+        .unwrap();
+    let data = client.get_share_data(share_id).await.unwrap();
+    assert_eq!(data, items(5));
+
+    // Batches above the server's limit are rejected, not retried
+    let err = client
+        .clone()
+        .with_retry(fast_retry(3))
+        .with_batch_items(3)
+        .sync_share(&share.id, &share.secret, &items(3))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(413));
+
+    client.remove_share(&share.id, &share.secret).await.unwrap();
+    assert!(client.get_share_data(share_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_client_retries_server_errors() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/api/share/:share_id/data",
+            get(|State(attempts): State<Arc<AtomicUsize>>| async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                Ok(Json(json!([{ "_key": "session", "type": "session", "data": {} }])))
+            }),
+        )
+        .with_state(attempts.clone());
+    let base_url = spawn(app).await;
+
+    let data = ShareClient::new(&base_url)
+        .with_retry(fast_retry(3))
+        .get_share_data("ses_1")
+        .await
+        .unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    attempts.store(0, Ordering::SeqCst);
+    let err = ShareClient::new(&base_url)
+        .with_retry(fast_retry(1))
+        .get_share_data("ses_1")
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(503));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_client_does_not_retry_create() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/api/share",
+            post(|State(attempts): State<Arc<AtomicUsize>>| async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }),
+        )
+        .with_state(attempts.clone());
+    let base_url = spawn(app).await;
+
+    // The share may have been created before the error, so a retry could create it twice
+    let err = ShareClient::new(&base_url)
+        .with_retry(fast_retry(3))
+        .create_share("ses_1")
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(503));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_client_honors_retry_after() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/api/share/:share_id/data",
+            get(|State(attempts): State<Arc<AtomicUsize>>| async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "1")]).into_response();
+                }
+                Json(json!([])).into_response()
+            }),
+        )
+        .with_state(attempts.clone());
+    let base_url = spawn(app).await;
+
+    let started = Instant::now();
+    ShareClient::new(&base_url)
+        .with_retry(fast_retry(1))
+        .get_share_data("ses_1")
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_client_batches_by_size() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route(
+            "/api/share/:share_id/sync",
+            post(
+                |State(batches): State<Arc<Mutex<Vec<usize>>>>,
+                 Path(_): Path<String>,
+                 Json(request): Json<SyncShareRequest>| async move {
+                    batches.lock().unwrap().push(request.data.len());
+                },
+            ),
+        )
+        .with_state(batches.clone());
+    let base_url = spawn(app).await;
+
+    let item_bytes = serde_json::to_vec(&items(1)[0]).unwrap().len();
+    ShareClient::new(&base_url)
+        .with_batch_bytes(item_bytes * 2)
+        .sync_share("ses_1", "s", &items(5))
+        .await
+        .unwrap();
+    assert_eq!(*batches.lock().unwrap(), [2, 2, 1]);
+
+    // Nothing to send, no requests
+    batches.lock().unwrap().clear();
+    ShareClient::new(&base_url)
+        .sync_share("ses_1", "s", &[])
+        .await
+        .unwrap();
+    assert!(batches.lock().unwrap().is_empty());
+}
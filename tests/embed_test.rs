@@ -0,0 +1,230 @@
+// Tests for the embeddable share view and the oEmbed provider
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use opencode_share::core::render::{render_embed, EmbedOptions};
+use opencode_share::core::transcript::Transcript;
+use opencode_share::routes::{embed_routes, share_routes};
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+fn sample_items() -> Vec<Value> {
+    let mut items = vec![json!({
+        "type": "session",
+        "data": { "id": "ses_embed", "title": "Fix <the> parser", "time": { "created": 1_700_000_000_000_i64 } }
+    })];
+    for i in 1..=4 {
+        items.push(json!({
+            "type": "message",
+            "data": {
+                "id": format!("msg_{}", i),
+                "sessionID": "ses_embed",
+                "role": if i % 2 == 1 { "user" } else { "assistant" },
+                "time": { "created": 1_700_000_000_000_i64 + i * 1000 }
+            }
+        }));
+        items.push(json!({
+            "type": "part",
+            "data": {
+                "id": format!("prt_{}", i),
+                "messageID": format!("msg_{}", i),
+                "type": "text",
+                "text": format!("Message number {}", i)
+            }
+        }));
+    }
+    items
+}
+
+async fn setup(share_id: &str, settings: Settings) -> (Router, AppState, String) {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, settings).expect("Failed to build app state");
+    let share_service = state.share_service();
+    let share = share_service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    share_service
+        .sync(share_id, &share.secret, sample_items())
+        .await
+        .expect("Failed to sync share");
+
+    let app = Router::new()
+        .nest("/share", share_routes())
+        .merge(embed_routes())
+        .with_state(state.clone());
+    (app, state, share.secret)
+}
+
+async fn get(app: &Router, uri: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("host", "share.example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[test]
+fn test_render_embed_message_range() {
+    let transcript = Transcript::from_items(&sample_items());
+    let options = EmbedOptions {
+        share_url: "/share/abc".to_string(),
+        from: Some(2),
+        to: Some(3),
+    };
+    let html = render_embed(&transcript, &options);
+
+    assert!(html.starts_with(r#"<div class="share-container embed">"#));
+    assert!(html.contains(r#"<a class="embed-title" href="/share/abc" target="_blank" rel="noopener">Fix &lt;the&gt; parser</a>"#));
+    assert!(!html.contains("Message number 1"));
+    assert!(html.contains("Message number 2"));
+    assert!(html.contains("Message number 3"));
+    assert!(!html.contains("Message number 4"));
+    assert!(!html.contains("session-stats"));
+}
+
+#[test]
+fn test_render_embed_clamps_range() {
+    let transcript = Transcript::from_items(&sample_items());
+    let embed = |from, to| {
+        render_embed(
+            &transcript,
+            &EmbedOptions {
+                share_url: "/share/abc".to_string(),
+                from,
+                to,
+            },
+        )
+    };
+
+    let all = embed(None, None);
+    assert!(all.contains("Message number 1") && all.contains("Message number 4"));
+    assert_eq!(embed(Some(0), Some(99)), all);
+    assert!(!embed(Some(3), Some(2)).contains("Message number"));
+    assert!(!embed(Some(9), None).contains("Message number"));
+}
+
+#[tokio::test]
+async fn test_embed_view() {
+    let share_id = "test-session-embed";
+    let mut settings = Settings::default();
+    settings.embed.frame_ancestors = vec!["'self'".to_string(), "https://blog.example.com".to_string()];
+    let (app, state, secret) = setup(share_id, settings).await;
+
+    let response = get(&app, &format!("/share/{}/embed?from=2&to=2", share_id)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_SECURITY_POLICY],
+        "frame-ancestors 'self' https://blog.example.com"
+    );
+    let html = body_string(response).await;
+    assert!(html.contains(r#"<body class="embed">"#));
+    assert!(html.contains("Message number 2"));
+    assert!(!html.contains("Message number 3"));
+
+    let response = get(&app, "/share/test-session-embed-missing/embed").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    state
+        .share_service()
+        .remove(share_id, &secret)
+        .await
+        .expect("Failed to remove share");
+}
+
+#[tokio::test]
+async fn test_oembed() {
+    let share_id = "test-session-oembed";
+    let (app, state, secret) = setup(share_id, Settings::default()).await;
+
+    let response = get(
+        &app,
+        &format!(
+            "/oembed?url=https%3A%2F%2Fshare.example.com%2Fshare%2F{}&maxwidth=640",
+            share_id
+        ),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["type"], "rich");
+    assert_eq!(body["version"], "1.0");
+    assert_eq!(body["title"], "Fix <the> parser");
+    assert_eq!(body["provider_url"], "https://share.example.com");
+    assert_eq!(body["width"], 640);
+    assert_eq!(body["height"], 600);
+    assert_eq!(
+        body["thumbnail_url"],
+        format!("https://share.example.com/share/{}/og.png", share_id)
+    );
+    let html = body["html"].as_str().unwrap();
+    assert!(html.contains(&format!(
+        r#"src="https://share.example.com/share/{}/embed" width="640" height="600""#,
+        share_id
+    )));
+    assert!(html.contains(r#"title="Fix &lt;the&gt; parser""#));
+
+    // Share URLs on another host, non-share URLs and unknown shares are not embeddable
+    for url in [
+        format!("https://other.example.com/share/{}", share_id),
+        format!("https://share.example.com.evil/share/{}", share_id),
+        format!("https://share.example.com/api/share/{}", share_id),
+        "https://share.example.com/share/test-session-oembed-missing".to_string(),
+    ] {
+        let response = get(&app, &format!("/oembed?url={}", url)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", url);
+    }
+
+    let response = get(
+        &app,
+        &format!("/oembed?url=https://share.example.com/share/{}&format=xml", share_id),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+
+    // Share pages advertise the endpoint for oEmbed discovery
+    let html = body_string(get(&app, &format!("/share/{}", share_id)).await).await;
+    assert!(html.contains(&format!(
+        r#"<link rel="alternate" type="application/json+oembed" href="https:&#x2f;&#x2f;share.example.com&#x2f;oembed?url=https%3A&#x2f;&#x2f;share.example.com&#x2f;share&#x2f;{}""#,
+        share_id
+    )));
+
+    state
+        .share_service()
+        .remove(share_id, &secret)
+        .await
+        .expect("Failed to remove share");
+}
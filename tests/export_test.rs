@@ -0,0 +1,357 @@
+// Tests for share exports
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use opencode_share::core::markdown::render_markdown;
+use opencode_share::core::transcript::Transcript;
+use opencode_share::routes::api_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+fn sample_items() -> Vec<Value> {
+    vec![
+        json!({
+            "type": "session",
+            "data": {
+                "id": "ses_export",
+                "title": "Fix the parser",
+                "version": "0.9.1",
+                "directory": "/home/dev/project",
+                "time": { "created": 1_700_000_000_000_i64 }
+            }
+        }),
+        json!({
+            "type": "message",
+            "data": {
+                "id": "msg_1",
+                "sessionID": "ses_export",
+                "role": "user",
+                "time": { "created": 1_700_000_001_000_i64 }
+            }
+        }),
+        json!({
+            "type": "part",
+            "data": { "id": "prt_1", "messageID": "msg_1", "type": "text", "text": "Why does `parse` fail on ```fences```?" }
+        }),
+        json!({
+            "type": "message",
+            "data": {
+                "id": "msg_2",
+                "sessionID": "ses_export",
+                "role": "assistant",
+                "modelID": "claude-sonnet",
+                "time": { "created": 1_700_000_002_000_i64 },
+                "tokens": { "input": 120, "output": 45 }
+            }
+        }),
+        json!({ "type": "part", "data": { "id": "prt_2", "messageID": "msg_2", "type": "step-start" } }),
+        json!({
+            "type": "part",
+            "data": { "id": "prt_3", "messageID": "msg_2", "type": "reasoning", "text": "Look at the lexer first." }
+        }),
+        json!({
+            "type": "part",
+            "data": {
+                "id": "prt_4",
+                "messageID": "msg_2",
+                "type": "tool",
+                "tool": "bash",
+                "state": {
+                    "status": "completed",
+                    "input": { "command": "cargo test", "description": "Run tests" },
+                    "output": "ok",
+                    "metadata": { "exit": 0 },
+                    "time": { "start": 1000, "end": 1250 }
+                }
+            }
+        }),
+        json!({
+            "type": "part",
+            "data": { "id": "prt_5", "messageID": "msg_2", "type": "text", "text": "The lexer **drops** the closing fence." }
+        }),
+        json!({
+            "type": "part",
+            "data": {
+                "id": "prt_6",
+                "messageID": "msg_2",
+                "type": "step-finish",
+                "tokens": { "input": 120, "output": 45, "cost": 0.0015 }
+            }
+        }),
+        json!({
+            "type": "session_diff",
+            "data": [{ "file": "src/lib.rs", "before": "a\nb\nc\n", "after": "a\nB\nc\nd\n" }]
+        }),
+    ]
+}
+
+async fn setup(share_id: &str) -> (Router, AppState, String) {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    let share_service = state.share_service();
+    let share = share_service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    share_service
+        .sync(share_id, &share.secret, sample_items())
+        .await
+        .expect("Failed to sync share");
+
+    let app = Router::new()
+        .nest("/api", api_routes())
+        .with_state(state.clone());
+    (app, state, share.secret)
+}
+
+async fn get(app: &Router, uri: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[test]
+fn test_render_markdown() {
+    let md = render_markdown(&Transcript::from_items(&sample_items()));
+
+    assert_eq!(
+        md,
+        r#"# Fix the parser
+
+v0.9.1 • 2023-11-14 • `/home/dev/project`
+
+**Messages:** 2 • **Session:** `ses_export`
+
+---
+
+## 👤 User · 2023-11-14 22:13:21 UTC
+
+Why does `parse` fail on ```fences```?
+
+---
+
+## 🤖 Assistant · `claude-sonnet` · 2023-11-14 22:13:22 UTC · 📥 120 📤 45
+
+> 🚀 Step Started
+
+<details>
+<summary>🤔 Thinking Process</summary>
+
+```
+Look at the lexer first.
+```
+
+</details>
+
+**🔧 bash - Run tests** · `completed`
+
+Input:
+
+```bash
+$ cargo test
+```
+
+Output:
+
+```
+ok
+```
+
+*Exit: 0 • ⏱ 250ms*
+
+The lexer **drops** the closing fence.
+
+> ✅ Step Completed (📥 Input: 120, 📤 Output: 45, 💰 Cost: $0.001500)
+
+---
+
+## 1 Files Changed
+
+### `src/lib.rs`
+
+```diff
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ a
+-b
++B
+ c
++d
+```
+"#
+    );
+}
+
+#[test]
+fn test_render_markdown_fences_and_long_output() {
+    let mut items = sample_items();
+    items.push(json!({
+        "type": "part",
+        "data": {
+            "id": "prt_7",
+            "messageID": "msg_2",
+            "type": "tool",
+            "tool": "read",
+            "state": {
+                "status": "completed",
+                "input": { "filePath": "README.md" },
+                "output": (1..=12).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n") + "\n```rust\n```"
+            }
+        }
+    }));
+    let md = render_markdown(&Transcript::from_items(&items));
+
+    assert!(md.contains("```json\n{\n  \"filePath\": \"README.md\"\n}\n```"));
+    // Output containing a fence gets a longer one so it cannot close the block early
+    assert!(md.contains("<details>\n<summary>Output (14 lines)</summary>\n\n````\nline 1\n"));
+    assert!(md.contains("```rust\n```\n````\n\n</details>"));
+}
+
+#[test]
+fn test_render_markdown_skips_invalid_tool_durations() {
+    let mut items = sample_items();
+    for (id, start, end) in [("prt_7", 2000, 1000), ("prt_8", i64::MIN + 1, i64::MAX)] {
+        items.push(json!({
+            "type": "part",
+            "data": {
+                "id": id,
+                "messageID": "msg_2",
+                "type": "tool",
+                "tool": "read",
+                "state": { "status": "completed", "time": { "start": start, "end": end } }
+            }
+        }));
+    }
+    let md = render_markdown(&Transcript::from_items(&items));
+
+    assert_eq!(md.matches("⏱").count(), 1);
+    assert!(md.contains("⏱ 250ms"));
+}
+
+#[test]
+fn test_render_markdown_without_session() {
+    let md = render_markdown(&Transcript::from_items(&[]));
+    assert!(md.starts_with("# Share Data Missing"));
+}
+
+#[tokio::test]
+async fn test_export_markdown_endpoint() {
+    let share_id = "test-session-export-md";
+    let (app, state, secret) = setup(share_id).await;
+
+    let response = get(&app, &format!("/api/share/{}/export.md", share_id)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/markdown; charset=utf-8"
+    );
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "inline; filename=\"test-session-export-md.md\""
+    );
+    let md = body_string(response).await;
+    assert!(md.starts_with("# Fix the parser\n"));
+
+    let response = get(&app, "/api/share/test-session-export-missing/export.md").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    state
+        .share_service()
+        .remove(share_id, &secret)
+        .await
+        .expect("Failed to remove share");
+}
+
+#[tokio::test]
+async fn test_export_filename_is_sanitized() {
+    let share_id = "test-session-export-\"; name=x.sh";
+    let (app, state, secret) = setup(share_id).await;
+
+    let response = get(&app, "/api/share/test-session-export-%22%3B%20name%3Dx.sh/export.md").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "inline; filename=\"test-session-export-namexsh.md\""
+    );
+
+    let response = get(&app, "/api/share/test-session-export-%22%3B%20name%3Dx.sh/export.html").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"test-session-export-namexsh.html\""
+    );
+
+    state
+        .share_service()
+        .remove(share_id, &secret)
+        .await
+        .expect("Failed to remove share");
+}
+
+#[tokio::test]
+async fn test_export_html_endpoint() {
+    let share_id = "test-session-export-html";
+    let (app, state, secret) = setup(share_id).await;
+
+    let response = get(&app, &format!("/api/share/{}/export.html", share_id)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"test-session-export-html.html\""
+    );
+    let html = body_string(response).await;
+
+    assert!(html.contains("<title>Fix the parser - Opencode Share</title>"));
+    assert!(html.contains(r#"<div class="session-title">Fix the parser</div>"#));
+    // Styles and script are inlined rather than linked
+    assert!(html.contains("--background-base"));
+    assert!(html.contains("class SharePage"));
+    assert!(!html.contains(r#"href="/static/"#));
+    assert!(!html.contains(r#"src="/static/"#));
+
+    // Only the rendered page is included, not a second copy of the raw items
+    assert!(!html.contains(r#"id="share-data""#));
+
+    let response = get(&app, "/api/share/test-session-export-missing/export.html").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    state
+        .share_service()
+        .remove(share_id, &secret)
+        .await
+        .expect("Failed to remove share");
+}
@@ -0,0 +1,98 @@
+// Integration tests for health, readiness and version endpoints
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use opencode_share::routes::health_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn get_test_pool() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    pool
+}
+
+fn test_app(pool: PgPool) -> Router {
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    Router::new().merge(health_routes()).with_state(state)
+}
+
+async fn get_json(app: Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.expect("Failed to get response");
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_healthz() {
+    let app = test_app(get_test_pool().await);
+
+    let (status, body) = get_json(app, "/healthz").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+}
+
+#[tokio::test]
+async fn test_readyz_with_migrated_database() {
+    let app = test_app(get_test_pool().await);
+
+    let (status, body) = get_json(app, "/readyz").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["database"], "ok");
+    assert_eq!(body["checks"]["migrations"], "ok");
+}
+
+#[tokio::test]
+async fn test_readyz_with_unreachable_database() {
+    // Nothing listens on port 1, so every connection attempt fails
+    let pool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(1))
+        .connect_lazy("postgres://postgres@127.0.0.1:1/opencode_share_test")
+        .unwrap();
+    let app = test_app(pool);
+
+    let (status, body) = get_json(app, "/readyz").await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["checks"]["database"], "unreachable");
+}
+
+#[tokio::test]
+async fn test_version() {
+    let app = test_app(get_test_pool().await);
+
+    let (status, body) = get_json(app, "/version").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "opencode-share");
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(!body["git_sha"].as_str().unwrap().is_empty());
+    assert!(body["features"].is_array());
+}
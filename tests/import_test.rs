@@ -0,0 +1,248 @@
+// Tests for importing `opencode export` sessions as shares
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use opencode_share::core::import::SessionExport;
+use opencode_share::core::limits::{LimitError, Limits};
+use opencode_share::core::share::{ShareExists, ShareService};
+use opencode_share::core::transcript::Transcript;
+use opencode_share::routes::api_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+fn sample_export(session_id: &str) -> Value {
+    json!({
+        "info": {
+            "id": session_id,
+            "title": "Imported session",
+            "version": "0.9.1",
+            "time": { "created": 1_700_000_000_000_i64, "updated": 1_700_000_010_000_i64 }
+        },
+        "messages": [
+            {
+                "info": {
+                    "id": "msg_1",
+                    "sessionID": session_id,
+                    "role": "user",
+                    "time": { "created": 1_700_000_001_000_i64 }
+                },
+                "parts": [
+                    { "id": "prt_1", "messageID": "msg_1", "sessionID": session_id, "type": "text", "text": "Fix the parser" }
+                ]
+            },
+            {
+                // Older exports leave out the back-references
+                "info": {
+                    "id": "msg_2",
+                    "role": "assistant",
+                    "modelID": "claude-sonnet",
+                    "time": { "created": 1_700_000_002_000_i64 }
+                },
+                "parts": [
+                    { "id": "prt_2", "type": "text", "text": "Done." },
+                    { "id": "prt_3", "type": "step-finish", "tokens": { "input": 10, "output": 5 } }
+                ]
+            }
+        ]
+    })
+}
+
+async fn get_test_pool() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+async fn delete_share(pool: &PgPool, share_id: &str) {
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(pool)
+        .await
+        .expect("Failed to clean test database");
+}
+
+#[test]
+fn test_export_into_items() {
+    let export: SessionExport = serde_json::from_value(sample_export("ses_import")).unwrap();
+    assert_eq!(export.session_id(), Some("ses_import"));
+
+    let items = export.into_items();
+    let keys: Vec<&str> = items.iter().map(|item| item["_key"].as_str().unwrap()).collect();
+    assert_eq!(
+        keys,
+        ["session", "message/msg_1", "part/prt_1", "message/msg_2", "part/prt_2", "part/prt_3"]
+    );
+    assert_eq!(items[0]["type"], "session");
+    assert_eq!(items[0]["data"]["title"], "Imported session");
+    assert_eq!(items[3]["data"]["sessionID"], "ses_import");
+    assert_eq!(items[4]["data"]["messageID"], "msg_2");
+
+    // The items render like a live-synced session
+    let transcript = Transcript::from_items(&items);
+    assert_eq!(transcript.messages().len(), 2);
+    assert_eq!(transcript.parts("msg_2").len(), 2);
+    assert_eq!(transcript.first_prompt().as_deref(), Some("Fix the parser"));
+}
+
+#[test]
+fn test_export_without_session_id() {
+    let export: SessionExport =
+        serde_json::from_value(json!({ "info": { "title": "No ID" } })).unwrap();
+    assert_eq!(export.session_id(), None);
+}
+
+#[tokio::test]
+async fn test_import_syncs_in_batches() {
+    let share_id = "test-session-import-batches";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let service = ShareService::new(pool).with_limits(Limits {
+        max_sync_items: 4,
+        ..Limits::default()
+    });
+    let export: SessionExport = serde_json::from_value(sample_export(share_id)).unwrap();
+
+    let share = service.import(export.clone()).await.expect("Failed to import share");
+    assert_eq!(share.id, share_id);
+    assert_eq!(share.data.unwrap().as_array().unwrap().len(), 6);
+
+    // A session can only be published once
+    let err = service.import(export).await.unwrap_err();
+    assert!(err.downcast_ref::<ShareExists>().is_some());
+
+    service
+        .remove(share_id, &share.secret)
+        .await
+        .expect("Failed to remove share");
+}
+
+#[tokio::test]
+async fn test_import_over_limits_leaves_no_share() {
+    let share_id = "test-session-import-quota";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let service = ShareService::new(pool).with_limits(Limits {
+        max_share_bytes: 256,
+        ..Limits::default()
+    });
+    let export: SessionExport = serde_json::from_value(sample_export(share_id)).unwrap();
+
+    let err = service.import(export).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LimitError>(),
+        Some(LimitError::QuotaExceeded { max: 256, .. })
+    ));
+    assert!(service.get(share_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_import_keeps_limit_error_when_cleanup_fails() {
+    let share_id = "test-session-import-cleanup";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    // Make the rollback delete of this one share fail
+    sqlx::query(
+        "CREATE OR REPLACE FUNCTION test_block_import_cleanup() RETURNS trigger AS $$ BEGIN RAISE EXCEPTION 'delete blocked'; END; $$ LANGUAGE plpgsql",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("DROP TRIGGER IF EXISTS test_block_import_cleanup ON shares")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "CREATE TRIGGER test_block_import_cleanup BEFORE DELETE ON shares FOR EACH ROW WHEN (OLD.id = 'test-session-import-cleanup') EXECUTE FUNCTION test_block_import_cleanup()",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let service = ShareService::new(pool.clone()).with_limits(Limits {
+        max_share_bytes: 256,
+        ..Limits::default()
+    });
+    let export: SessionExport = serde_json::from_value(sample_export(share_id)).unwrap();
+    let err = service.import(export).await.unwrap_err();
+
+    sqlx::query("DROP TRIGGER test_block_import_cleanup ON shares")
+        .execute(&pool)
+        .await
+        .unwrap();
+    delete_share(&pool, share_id).await;
+
+    assert!(matches!(
+        err.downcast_ref::<LimitError>(),
+        Some(LimitError::QuotaExceeded { max: 256, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_import_endpoint() {
+    let share_id = "test-session-import-api";
+    let pool = get_test_pool().await;
+    delete_share(&pool, share_id).await;
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    let app = Router::new()
+        .nest("/api", api_routes())
+        .with_state(state.clone());
+    let import = |body: Value| {
+        app.clone().oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/api/share/import")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header("host", "share.example.com")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+
+    let response = import(sample_export(share_id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let created: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(created["id"], share_id);
+    assert_eq!(
+        created["url"],
+        format!("https://share.example.com/share/{}", share_id)
+    );
+    let secret = created["secret"].as_str().unwrap();
+
+    let data = state.share_service().get_data(share_id).await.unwrap();
+    assert_eq!(data.len(), 6);
+
+    let response = import(sample_export(share_id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = import(json!({ "info": { "title": "No ID" }, "messages": [] }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    state
+        .share_service()
+        .remove(share_id, secret)
+        .await
+        .expect("Failed to remove share");
+}
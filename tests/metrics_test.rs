@@ -0,0 +1,131 @@
+// Integration tests for the Prometheus metrics endpoint
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    middleware::from_fn_with_state,
+    Router,
+};
+use opencode_share::middleware::metrics_middleware;
+use opencode_share::routes::{api_routes, health_routes};
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+async fn get_test_app() -> Router {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    sqlx::query("DELETE FROM shares WHERE session_id LIKE 'test-session-metrics%'")
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    Router::new()
+        .nest("/api", api_routes())
+        .merge(health_routes())
+        .layer(from_fn_with_state(state.clone(), metrics_middleware))
+        .with_state(state)
+}
+
+async fn send(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, String) {
+    let mut request = Request::builder().method(method).uri(uri);
+    let body = match body {
+        Some(body) => {
+            let body = serde_json::to_string(&body).unwrap();
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::CONTENT_LENGTH, body.len());
+            Body::from(body)
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .expect("Failed to get response");
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_metrics_record_share_traffic() {
+    let app = get_test_app().await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/share",
+        Some(json!({ "sessionID": "test-session-metrics" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let share: Value = serde_json::from_str(&body).unwrap();
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/share/{}/sync", share["id"].as_str().unwrap()),
+        Some(json!({
+            "secret": share["secret"],
+            "data": [
+                { "_key": "session", "title": "Metrics" },
+                { "_key": "message/msg-1", "role": "user" }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, metrics) = send(&app, Method::GET, "/metrics", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Labelled by route template rather than share ID
+    assert!(metrics.contains(
+        r#"opencode_share_http_requests_total{method="POST",route="/api/share/:share_id/sync",status="200"} 1"#
+    ));
+    assert!(metrics.contains(
+        r#"opencode_share_http_request_duration_seconds_count{method="POST",route="/api/share",status="200"} 1"#
+    ));
+    assert!(metrics
+        .contains(r#"opencode_share_http_request_size_bytes_count{method="POST",route="/api/share/:share_id/sync"} 1"#));
+    assert!(metrics.contains("opencode_share_sync_items_sum 2"));
+    assert!(metrics.contains("opencode_share_shares_created_total 1"));
+    assert!(metrics.contains("opencode_share_db_pool_max_connections 10"));
+    assert!(metrics.contains(r#"opencode_share_db_pool_connections{state="idle"}"#));
+    assert!(metrics.contains("opencode_share_shares "));
+    assert!(!metrics.contains("opencode_share_sse_subscribers"));
+}
+
+#[tokio::test]
+async fn test_metrics_group_unmatched_routes() {
+    let app = get_test_app().await;
+
+    let (status, _) = send(&app, Method::GET, "/no/such/path", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, metrics) = send(&app, Method::GET, "/metrics", None).await;
+
+    assert!(metrics.contains(
+        r#"opencode_share_http_requests_total{method="GET",route="unmatched",status="404"} 1"#
+    ));
+    assert!(!metrics.contains("/no/such/path"));
+}
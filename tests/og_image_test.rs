@@ -0,0 +1,198 @@
+// Tests for generated social preview images
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, Utc};
+use opencode_share::core::og_image::{preview_svg, OgImageRenderer};
+use opencode_share::core::transcript::Transcript;
+use opencode_share::routes::share_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn sample_items(title: &str) -> Vec<Value> {
+    vec![
+        json!({
+            "type": "session",
+            "data": { "id": "ses_og", "title": title, "time": { "created": 1_700_000_000_000_i64 } }
+        }),
+        json!({
+            "type": "message",
+            "data": {
+                "id": "msg_1",
+                "sessionID": "ses_og",
+                "role": "user",
+                "time": { "created": 1_700_000_001_000_i64 }
+            }
+        }),
+        json!({
+            "type": "message",
+            "data": {
+                "id": "msg_2",
+                "sessionID": "ses_og",
+                "role": "assistant",
+                "modelID": "claude-sonnet",
+                "cost": 0.42,
+                "time": { "created": 1_700_000_002_000_i64 },
+                "tokens": { "input": 1200, "output": 300 }
+            }
+        }),
+        json!({
+            "type": "session_diff",
+            "data": [
+                { "file": "a.rs", "before": "", "after": "x" },
+                { "file": "b.rs", "before": "y", "after": "" }
+            ]
+        }),
+    ]
+}
+
+/// Width and height from a PNG's IHDR chunk.
+fn png_size(png: &[u8]) -> (u32, u32) {
+    assert!(png.starts_with(PNG_SIGNATURE));
+    let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    (width, height)
+}
+
+#[test]
+fn test_preview_svg() {
+    let svg = preview_svg(&Transcript::from_items(&sample_items("Fix <the> parser & lexer")));
+
+    assert!(svg.contains("Fix &lt;the&gt; parser &amp;"));
+    assert!(svg.contains(">claude-sonnet<"));
+    assert!(svg.contains("2 messages  ·  1.5k tokens  ·  $0.42  ·  2 files changed"));
+}
+
+#[test]
+fn test_preview_svg_wraps_long_titles() {
+    let title = "word ".repeat(40);
+    let svg = preview_svg(&Transcript::from_items(&sample_items(&title)));
+
+    assert_eq!(svg.matches("<tspan").count(), 3);
+    assert!(svg.contains("…</tspan>"));
+}
+
+#[test]
+fn test_preview_svg_saturates_token_totals() {
+    let mut items = sample_items("Fix the parser");
+    items.push(json!({
+        "type": "message",
+        "data": {
+            "id": "msg_3",
+            "sessionID": "ses_og",
+            "role": "assistant",
+            "time": { "created": 1_700_000_003_000_i64 },
+            "tokens": { "input": i64::MAX, "output": i64::MAX, "reasoning": i64::MAX }
+        }
+    }));
+    let transcript = Transcript::from_items(&items);
+
+    assert_eq!(transcript.usage().input_tokens, i64::MAX);
+    assert_eq!(transcript.usage().total_tokens(), i64::MAX);
+    assert!(preview_svg(&transcript).contains("3 messages"));
+}
+
+#[test]
+fn test_render_and_cache_by_revision() {
+    let renderer = OgImageRenderer::new();
+    let transcript = Transcript::from_items(&sample_items("Fix the parser"));
+    let revision = Utc::now();
+
+    assert!(renderer.cached("abc", revision).is_none());
+
+    let png = renderer
+        .render("abc", revision, &transcript)
+        .expect("Failed to render preview image");
+    assert_eq!(png_size(&png), (1200, 630));
+
+    assert_eq!(renderer.cached("abc", revision), Some(png));
+    assert!(renderer.cached("abc", revision + Duration::seconds(1)).is_none());
+}
+
+#[tokio::test]
+async fn test_og_image_endpoint() {
+    let share_id = "test-session-og-image";
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    let share_service = state.share_service();
+    let share = share_service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    share_service
+        .sync(share_id, &share.secret, sample_items("Fix the parser"))
+        .await
+        .expect("Failed to sync share");
+
+    let app = Router::new()
+        .nest("/share", share_routes())
+        .with_state(state);
+    let get = |if_none_match: Option<String>| {
+        let mut request = Request::builder().uri(format!("/share/{}/og.png", share_id));
+        if let Some(etag) = if_none_match {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    let response = get(None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+    let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(png_size(&body), (1200, 630));
+
+    let response = get(Some(etag.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // A sync is a new revision, so the cached image no longer matches
+    share_service
+        .sync(share_id, &share.secret, sample_items("Fix the lexer"))
+        .await
+        .expect("Failed to sync share");
+    let response = get(Some(etag.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()[header::ETAG].to_str().unwrap(), etag);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/share/test-session-og-missing/og.png")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    share_service
+        .remove(share_id, &share.secret)
+        .await
+        .expect("Failed to remove share");
+}
@@ -0,0 +1,299 @@
+// Tests for patch and changed-file exports of session diffs
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use opencode_share::core::patch::{file_after, session_patch};
+use opencode_share::routes::api_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::{env, fs, process::Command};
+use tower::ServiceExt;
+
+fn sample_diffs() -> Vec<Value> {
+    vec![
+        json!({ "file": "src/lib.rs", "before": "a\nb\nc\n", "after": "a\nB\nc\nd\n" }),
+        json!({ "file": "src/new.rs", "before": null, "after": "fn main() {}\n" }),
+        json!({ "file": "old.txt", "before": "gone\n", "after": null }),
+        json!({ "file": "README.md", "before": "title", "after": "title\nmore" }),
+        json!({ "file": "same.txt", "before": "x\n", "after": "x\n" }),
+        json!({ "file": "empty.txt", "before": "", "after": "filled\n" }),
+    ]
+}
+
+#[test]
+fn test_session_patch() {
+    let patch = session_patch(&sample_diffs(), None);
+
+    assert_eq!(
+        patch,
+        concat!(
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            "--- a/src/lib.rs\n",
+            "+++ b/src/lib.rs\n",
+            "@@ -1,3 +1,4 @@\n",
+            " a\n",
+            "-b\n",
+            "+B\n",
+            " c\n",
+            "+d\n",
+            "diff --git a/src/new.rs b/src/new.rs\n",
+            "new file mode 100644\n",
+            "--- /dev/null\n",
+            "+++ b/src/new.rs\n",
+            "@@ -0,0 +1 @@\n",
+            "+fn main() {}\n",
+            "diff --git a/old.txt b/old.txt\n",
+            "deleted file mode 100644\n",
+            "--- a/old.txt\n",
+            "+++ /dev/null\n",
+            "@@ -1 +0,0 @@\n",
+            "-gone\n",
+            "diff --git a/README.md b/README.md\n",
+            "--- a/README.md\n",
+            "+++ b/README.md\n",
+            "@@ -1 +1,2 @@\n",
+            "-title\n",
+            "\\ No newline at end of file\n",
+            "+title\n",
+            "+more\n",
+            "\\ No newline at end of file\n",
+            "diff --git a/empty.txt b/empty.txt\n",
+            "--- a/empty.txt\n",
+            "+++ b/empty.txt\n",
+            "@@ -0,0 +1 @@\n",
+            "+filled\n",
+        )
+    );
+}
+
+#[test]
+fn test_session_patch_applies_with_git() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    };
+
+    git(&["init", "--quiet"]);
+    fs::create_dir(dir.path().join("src")).unwrap();
+    for diff in sample_diffs() {
+        if let Some(before) = diff["before"].as_str() {
+            fs::write(dir.path().join(diff["file"].as_str().unwrap()), before).unwrap();
+        }
+    }
+    fs::write(dir.path().join("session.patch"), session_patch(&sample_diffs(), None)).unwrap();
+
+    git(&["apply", "session.patch"]);
+
+    for diff in sample_diffs() {
+        let path = dir.path().join(diff["file"].as_str().unwrap());
+        match diff["after"].as_str() {
+            Some(after) => assert_eq!(fs::read_to_string(path).unwrap(), after),
+            None => assert!(!path.exists()),
+        }
+    }
+}
+
+#[test]
+fn test_session_patch_status_field() {
+    let diffs = vec![
+        json!({ "file": "added.rs", "status": "added", "before": "", "after": "x\n" }),
+        json!({ "file": "deleted.rs", "status": "deleted", "before": "y\n", "after": "" }),
+        json!({ "file": "emptied.rs", "status": "modified", "before": "z\n", "after": "" }),
+    ];
+    let patch = session_patch(&diffs, None);
+
+    assert!(patch.contains("diff --git a/added.rs b/added.rs\nnew file mode 100644\n--- /dev/null\n"));
+    assert!(patch.contains("diff --git a/deleted.rs b/deleted.rs\ndeleted file mode 100644\n"));
+    assert!(patch.contains("diff --git a/emptied.rs b/emptied.rs\n--- a/emptied.rs\n+++ b/emptied.rs\n"));
+    assert_eq!(file_after(&diffs, None, "emptied.rs"), Some(""));
+    assert_eq!(file_after(&diffs, None, "deleted.rs"), None);
+}
+
+#[test]
+fn test_file_after() {
+    let diffs = sample_diffs();
+
+    assert_eq!(file_after(&diffs, None, "src/lib.rs"), Some("a\nB\nc\nd\n"));
+    assert_eq!(file_after(&diffs, None, "/src/new.rs"), Some("fn main() {}\n"));
+    assert_eq!(file_after(&diffs, None, "same.txt"), Some("x\n"));
+    assert_eq!(file_after(&diffs, None, "old.txt"), None);
+    assert_eq!(file_after(&diffs, None, "missing.rs"), None);
+}
+
+#[test]
+fn test_session_patch_paths_relative_to_root() {
+    let diffs = vec![
+        json!({ "file": "/home/dev/project/src/lib.rs", "before": "a\n", "after": "b\n" }),
+        json!({ "file": "/home/dev/project-other/x.rs", "before": "a\n", "after": "b\n" }),
+    ];
+    let patch = session_patch(&diffs, Some("/home/dev/project/"));
+
+    assert!(patch.contains("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n"));
+    // Only whole directory names are stripped
+    assert!(patch.contains("diff --git a/home/dev/project-other/x.rs b/home/dev/project-other/x.rs\n"));
+
+    assert_eq!(file_after(&diffs, Some("/home/dev/project"), "src/lib.rs"), Some("b\n"));
+    assert_eq!(
+        file_after(&diffs, Some("/home/dev/project"), "/home/dev/project/src/lib.rs"),
+        Some("b\n")
+    );
+}
+
+#[test]
+fn test_session_patch_quotes_paths() {
+    let diffs = vec![
+        json!({ "file": "docs/my notes.md", "before": "a\n", "after": "b\n" }),
+        json!({ "file": "src/\"q\"\\tab\t.rs", "before": null, "after": "x\n" }),
+        json!({ "file": "caf\u{e9}.txt", "before": "a\n", "after": null }),
+    ];
+    let patch = session_patch(&diffs, None);
+
+    assert!(patch.contains(concat!(
+        "diff --git \"a/docs/my notes.md\" \"b/docs/my notes.md\"\n",
+        "--- \"a/docs/my notes.md\"\n",
+        "+++ \"b/docs/my notes.md\"\n",
+    )));
+    assert!(patch.contains(concat!(
+        "diff --git \"a/src/\\\"q\\\"\\\\tab\\t.rs\" \"b/src/\\\"q\\\"\\\\tab\\t.rs\"\n",
+        "new file mode 100644\n",
+        "--- /dev/null\n",
+        "+++ \"b/src/\\\"q\\\"\\\\tab\\t.rs\"\n",
+    )));
+    assert!(patch.contains("--- \"a/caf\\303\\251.txt\"\n+++ /dev/null\n"));
+}
+
+#[test]
+fn test_quoted_patch_applies_with_git() {
+    let dir = tempfile::tempdir().unwrap();
+    let diffs = vec![
+        json!({ "file": "/work/repo/my notes.md", "before": "a\n", "after": "b\n" }),
+        json!({ "file": "/work/repo/caf\u{e9}.txt", "before": null, "after": "x\n" }),
+    ];
+    fs::write(dir.path().join("my notes.md"), "a\n").unwrap();
+    fs::write(dir.path().join("session.patch"), session_patch(&diffs, Some("/work/repo"))).unwrap();
+
+    let output = Command::new("git")
+        .args(["apply", "session.patch"])
+        .current_dir(dir.path())
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(fs::read_to_string(dir.path().join("my notes.md")).unwrap(), "b\n");
+    assert_eq!(fs::read_to_string(dir.path().join("caf\u{e9}.txt")).unwrap(), "x\n");
+}
+
+#[tokio::test]
+async fn test_patch_and_file_endpoints() {
+    let share_id = "test-session-patch";
+    let odd_id = "test-session-patch\"; x";
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    sqlx::query("DELETE FROM shares WHERE id = ANY($1)")
+        .bind([share_id, odd_id])
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    let share_service = state.share_service();
+    let share = share_service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    share_service
+        .sync(
+            share_id,
+            &share.secret,
+            vec![
+                json!({ "type": "session", "data": { "id": "ses_patch", "title": "Patch" } }),
+                json!({ "type": "session_diff", "data": sample_diffs() }),
+            ],
+        )
+        .await
+        .expect("Failed to sync share");
+
+    let app = Router::new()
+        .nest("/api", api_routes())
+        .with_state(state);
+    let get = |uri: String| {
+        app.clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+    };
+    let body = |response: axum::response::Response| async {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    };
+
+    let response = get(format!("/api/share/{}/diff.patch", share_id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/x-diff; charset=utf-8"
+    );
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "inline; filename=\"test-session-patch.patch\""
+    );
+    assert_eq!(body(response).await, session_patch(&sample_diffs(), None));
+
+    let response = get(format!("/api/share/{}/files/src/lib.rs", share_id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/plain; charset=utf-8"
+    );
+    assert_eq!(body(response).await, "a\nB\nc\nd\n");
+
+    for path in ["old.txt", "src/missing.rs"] {
+        let response = get(format!("/api/share/{}/files/{}", share_id, path)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", path);
+    }
+
+    let response = get("/api/share/test-session-patch-missing/diff.patch".to_string())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Characters outside [A-Za-z0-9_-] are dropped from the download file name
+    let odd = share_service
+        .create(odd_id.to_string())
+        .await
+        .expect("Failed to create share");
+    let response = get("/api/share/test-session-patch%22%3B%20x/diff.patch".to_string())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "inline; filename=\"test-session-patchx.patch\""
+    );
+
+    share_service
+        .remove(odd_id, &odd.secret)
+        .await
+        .expect("Failed to remove share");
+    share_service
+        .remove(share_id, &share.secret)
+        .await
+        .expect("Failed to remove share");
+}
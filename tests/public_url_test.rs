@@ -0,0 +1,97 @@
+// Tests for public share URL generation
+
+use axum::http::HeaderMap;
+use opencode_share::core::public_url::{HostNotAllowed, PublicUrl};
+
+fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(*name, value.parse().unwrap());
+    }
+    headers
+}
+
+fn allowed(hosts: &[&str]) -> PublicUrl {
+    PublicUrl::new(None, hosts.iter().map(|h| h.to_string()).collect()).unwrap()
+}
+
+#[test]
+fn test_base_url_overrides_headers() {
+    let public_url = PublicUrl::new(Some("https://share.example.com/"), vec![]).unwrap();
+    let headers = headers(&[("host", "attacker.test"), ("x-forwarded-proto", "http")]);
+
+    assert_eq!(
+        public_url.base_url(&headers).unwrap(),
+        "https://share.example.com"
+    );
+}
+
+#[test]
+fn test_invalid_base_url() {
+    assert!(PublicUrl::new(Some("share.example.com"), vec![]).is_err());
+}
+
+#[test]
+fn test_headers_used_without_base_url() {
+    let public_url = PublicUrl::default();
+    let headers = headers(&[
+        ("host", "internal:3006"),
+        ("x-forwarded-host", "share.example.com"),
+        ("x-forwarded-proto", "http"),
+    ]);
+
+    assert_eq!(
+        public_url.base_url(&headers).unwrap(),
+        "http://share.example.com"
+    );
+}
+
+#[test]
+fn test_unknown_protocol_defaults_to_https() {
+    let public_url = PublicUrl::default();
+    let headers = headers(&[("host", "share.example.com"), ("x-forwarded-proto", "javascript")]);
+
+    assert_eq!(
+        public_url.base_url(&headers).unwrap(),
+        "https://share.example.com"
+    );
+}
+
+#[test]
+fn test_allowed_hosts() {
+    let public_url = allowed(&["share.example.com", "*.preview.example.com", "localhost:3006"]);
+
+    for host in [
+        "share.example.com",
+        "SHARE.example.com:8443",
+        "pr-12.preview.example.com",
+        "localhost:3006",
+    ] {
+        assert!(
+            public_url.base_url(&headers(&[("host", host)])).is_ok(),
+            "{} should be allowed",
+            host
+        );
+    }
+
+    for host in [
+        "evil.example.net",
+        "preview.example.com",
+        "evilpreview.example.com",
+        "localhost:3000",
+        "share.example.com.evil.net",
+    ] {
+        let err = public_url
+            .base_url(&headers(&[("host", host)]))
+            .unwrap_err();
+        assert!(err.downcast_ref::<HostNotAllowed>().is_some(), "{} should be rejected", host);
+    }
+}
+
+#[test]
+fn test_host_with_path_rejected() {
+    let public_url = PublicUrl::default();
+    let headers = headers(&[("x-forwarded-host", "evil.test/phish?")]);
+
+    assert!(public_url.base_url(&headers).is_err());
+}
@@ -0,0 +1,290 @@
+// Tests for server-side rendering of the share page
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use opencode_share::core::meta::{excerpt, ShareMeta};
+use opencode_share::core::render::{escape_html, render_transcript};
+use opencode_share::core::transcript::Transcript;
+use opencode_share::routes::share_routes;
+use opencode_share::settings::Settings;
+use opencode_share::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::env;
+use tower::ServiceExt;
+
+fn sample_items() -> Vec<Value> {
+    vec![
+        json!({
+            "type": "session",
+            "data": {
+                "id": "ses_render",
+                "title": "Fix <the> parser",
+                "version": "0.9.1",
+                "directory": "/home/dev/project",
+                "time": { "created": 1_700_000_000_000_i64 }
+            }
+        }),
+        // Synced out of order; rendered by creation time
+        json!({
+            "type": "message",
+            "data": {
+                "id": "msg_2",
+                "sessionID": "ses_render",
+                "role": "assistant",
+                "modelID": "claude-sonnet",
+                "time": { "created": 1_700_000_002_000_i64 },
+                "tokens": { "input": 120, "output": 45 }
+            }
+        }),
+        json!({
+            "type": "message",
+            "data": {
+                "id": "msg_1",
+                "sessionID": "ses_render",
+                "role": "user",
+                "time": { "created": 1_700_000_001_000_i64 }
+            }
+        }),
+        json!({
+            "type": "part",
+            "data": { "id": "prt_1", "messageID": "msg_1", "type": "text", "text": "Why does <script>alert(1)</script> fail?" }
+        }),
+        json!({ "type": "part", "data": { "id": "prt_2", "messageID": "msg_2", "type": "step-start" } }),
+        json!({
+            "type": "part",
+            "data": {
+                "id": "prt_3",
+                "messageID": "msg_2",
+                "type": "reasoning",
+                "text": "one\ntwo\nthree\nfour\nfive\nsix"
+            }
+        }),
+        json!({
+            "type": "part",
+            "data": {
+                "id": "prt_4",
+                "messageID": "msg_2",
+                "type": "tool",
+                "tool": "bash",
+                "state": {
+                    "status": "completed",
+                    "input": { "command": "cargo test", "description": "Run tests" },
+                    "output": "ok",
+                    "metadata": { "exit": 0 },
+                    "time": { "start": 1000, "end": 1250 }
+                }
+            }
+        }),
+        json!({
+            "type": "part",
+            "data": {
+                "id": "prt_5",
+                "messageID": "msg_2",
+                "type": "step-finish",
+                "tokens": { "input": 120, "output": 45, "cost": 0.0015 }
+            }
+        }),
+        json!({
+            "type": "session_diff",
+            "data": [{ "file": "src/lib.rs", "before": "a\nb\nc\n", "after": "a\nB\nc\nd\n" }]
+        }),
+    ]
+}
+
+#[test]
+fn test_render_transcript() {
+    let html = render_transcript(&Transcript::from_items(&sample_items()));
+
+    assert!(html.contains(r#"<div class="session-title">Fix &lt;the&gt; parser</div>"#));
+    assert!(html.contains(r#"<span class="stat-value">2</span>"#));
+    assert!(html.contains("Why does &lt;script&gt;alert(1)&lt;/script&gt; fail?"));
+    assert!(!html.contains("<script>"));
+
+    // Messages are ordered by creation time regardless of sync order
+    let user = html.find("👤 User").unwrap();
+    let assistant = html.find("🤖 Assistant").unwrap();
+    assert!(user < assistant);
+
+    assert!(html.contains(r#"<div class="step-marker step-start">🚀 Step Started</div>"#));
+    assert!(html.contains("✅ Step Completed (📥 Input: 120, 📤 Output: 45, 💰 Cost: $0.001500)"));
+    assert!(html.contains(r#"<div class="reasoning-content collapsible">"#));
+    assert!(html.contains(r#"<span class="tool-title">bash - Run tests</span>"#));
+    assert!(html.contains("<code>cargo test</code>"));
+    assert!(html.contains(r#"<span class="exit-success">Exit: 0</span>"#));
+    assert!(html.contains("⏱ 250ms"));
+    assert!(html.contains(r#"<time datetime="2023-11-14T22:13:21+00:00">2023-11-14 22:13:21 UTC</time>"#));
+}
+
+#[test]
+fn test_render_diff() {
+    let html = render_transcript(&Transcript::from_items(&sample_items()));
+
+    assert!(html.contains("1 Files Changed"));
+    assert!(html.contains(r#"<span class="diff-stats">+2 -1</span>"#));
+    assert!(html.contains(
+        r#"<div class="diff-row removed"><div class="diff-line-num">2</div><div class="diff-cell">b</div></div>"#
+    ));
+    assert!(html.contains(
+        r#"<div class="diff-row added"><div class="diff-line-num">4</div><div class="diff-cell">d</div></div>"#
+    ));
+}
+
+#[test]
+fn test_render_long_tool_output_is_collapsible() {
+    let output = (1..=12).map(|n| format!("line {}", n)).collect::<Vec<_>>().join("\n");
+    let mut items = sample_items();
+    items.push(json!({
+        "type": "part",
+        "data": {
+            "id": "prt_6",
+            "messageID": "msg_2",
+            "type": "tool",
+            "tool": "read",
+            "state": { "status": "completed", "output": output }
+        }
+    }));
+
+    let html = render_transcript(&Transcript::from_items(&items));
+
+    assert!(html.contains("Show full output (12 lines)"));
+    assert!(html.contains(r#"<div class="output-preview"><pre><code>line 1
+line 2
+line 3
+line 4
+line 5</code></pre></div>"#));
+    assert!(html.contains("line 12</code></pre></div>"));
+}
+
+#[test]
+fn test_render_skips_invalid_tool_durations() {
+    let mut items = sample_items();
+    for (id, start, end) in [("prt_6", 2000, 1000), ("prt_7", i64::MIN + 1, i64::MAX)] {
+        items.push(json!({
+            "type": "part",
+            "data": {
+                "id": id,
+                "messageID": "msg_2",
+                "type": "tool",
+                "tool": "read",
+                "state": { "status": "completed", "time": { "start": start, "end": end } }
+            }
+        }));
+    }
+
+    let html = render_transcript(&Transcript::from_items(&items));
+
+    assert_eq!(html.matches("tool-duration").count(), 1);
+    assert!(html.contains("⏱ 250ms"));
+}
+
+#[test]
+fn test_render_without_session() {
+    let html = render_transcript(&Transcript::from_items(&[]));
+    assert!(html.contains("Share Data Missing"));
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html(r#"<a href="x">'&'</a>"#),
+        "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+    );
+}
+
+#[test]
+fn test_share_meta() {
+    let meta = ShareMeta::new(
+        &Transcript::from_items(&sample_items()),
+        "abc",
+        Some("https://share.example.com"),
+    );
+
+    assert_eq!(meta.title, "Fix <the> parser");
+    assert_eq!(
+        meta.description,
+        "claude-sonnet · 2 messages — Why does <script>alert(1)</script> fail?"
+    );
+    assert_eq!(meta.url.as_deref(), Some("https://share.example.com/share/abc"));
+    assert_eq!(
+        meta.image.as_deref(),
+        Some("https://share.example.com/share/abc/og.png")
+    );
+
+    let empty = ShareMeta::new(&Transcript::from_items(&[]), "abc", None);
+    assert_eq!(empty.title, "Opencode Share");
+    assert_eq!(empty.description, "0 messages");
+    assert!(empty.url.is_none());
+}
+
+#[test]
+fn test_excerpt() {
+    assert_eq!(excerpt("  fix\n\nthe   parser ", 20), "fix the parser");
+    assert_eq!(excerpt("abcdefghij", 5), "abcd…");
+}
+
+#[tokio::test]
+async fn test_share_page_is_rendered_on_the_server() {
+    let share_id = "test-session-render";
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    let share_service = state.share_service();
+    let share = share_service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+    share_service
+        .sync(share_id, &share.secret, sample_items())
+        .await
+        .expect("Failed to sync share");
+
+    let app = Router::new()
+        .nest("/share", share_routes())
+        .with_state(state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/share/{}", share_id))
+                .header("host", "share.example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains(r#"<div id="app"><div class="share-container">"#));
+    assert!(html.contains("Why does &lt;script&gt;alert(1)&lt;/script&gt; fail?"));
+    assert!(html.contains(r#"<meta property="og:title" content="Fix &lt;the&gt; parser">"#));
+    assert!(html.contains(
+        r#"<meta property="og:url" content="https:&#x2f;&#x2f;share.example.com&#x2f;share&#x2f;test-session-render">"#
+    ));
+    assert!(html.contains(r#"<meta name="twitter:card" content="summary_large_image">"#));
+
+    share_service
+        .remove(share_id, &share.secret)
+        .await
+        .expect("Failed to remove share");
+}
@@ -0,0 +1,276 @@
+// Tests for listener binding and serving
+
+use axum::{extract::ConnectInfo, routing::get, Router};
+use opencode_share::server::{serve, Listener};
+use opencode_share::settings::{ServerSettings, Settings};
+use opencode_share::AppState;
+use serde_json::json;
+use sqlx::PgPool;
+use std::env;
+use std::future::pending;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::sync::{oneshot, Notify};
+
+fn test_router() -> Router {
+    Router::new().route(
+        "/peer",
+        get(|ConnectInfo(addr): ConnectInfo<SocketAddr>| async move { addr.ip().to_string() }),
+    )
+}
+
+async fn tcp_listener() -> (Listener, String) {
+    let settings = ServerSettings {
+        host: "127.0.0.1".to_string(),
+        port: 0,
+        ..ServerSettings::default()
+    };
+    let listener = Listener::bind(&settings)
+        .await
+        .expect("Failed to bind TCP listener");
+    let addr = listener.describe();
+    (listener, addr)
+}
+
+async fn http_get<S>(stream: S, path: &str) -> String
+where
+    S: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    send_request(stream, request).await
+}
+
+async fn http_post<S>(stream: S, path: &str, body: &str) -> String
+where
+    S: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        body.len(),
+        body
+    );
+    send_request(stream, request).await
+}
+
+async fn send_request<S>(mut stream: S, request: String) -> String
+where
+    S: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .expect("Failed to write request");
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .await
+        .expect("Failed to read response");
+    response
+}
+
+#[tokio::test]
+async fn test_serve_over_unix_socket() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("share.sock");
+
+    let settings = ServerSettings {
+        unix_socket: Some(path.clone()),
+        ..ServerSettings::default()
+    };
+    let listener = Listener::bind(&settings)
+        .await
+        .expect("Failed to bind Unix socket");
+    assert_eq!(listener.describe(), format!("unix:{}", path.display()));
+
+    tokio::spawn(serve(listener, test_router(), pending(), Duration::from_secs(1)));
+
+    let stream = UnixStream::connect(&path)
+        .await
+        .expect("Failed to connect to Unix socket");
+    let response = http_get(stream, "/peer").await;
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    // Unix socket peers are reported as loopback
+    assert!(response.ends_with("127.0.0.1"));
+}
+
+#[tokio::test]
+async fn test_unix_socket_replaces_stale_socket() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("share.sock");
+
+    // Leave a socket file behind, as after an unclean shutdown
+    let stale = std::os::unix::net::UnixListener::bind(&path).expect("Failed to bind");
+    drop(stale);
+    assert!(path.exists());
+
+    let listener = Listener::bind_unix(&path);
+
+    assert!(listener.is_ok());
+}
+
+#[tokio::test]
+async fn test_unix_socket_refuses_regular_file() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("not-a-socket");
+    std::fs::write(&path, "data").expect("Failed to write file");
+
+    let listener = Listener::bind_unix(&path);
+
+    assert!(listener.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "data");
+}
+
+#[tokio::test]
+async fn test_serve_over_tcp() {
+    let (listener, addr) = tcp_listener().await;
+
+    tokio::spawn(serve(listener, test_router(), pending(), Duration::from_secs(1)));
+
+    let stream = TcpStream::connect(&addr)
+        .await
+        .expect("Failed to connect to TCP listener");
+    let response = http_get(stream, "/peer").await;
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("127.0.0.1"));
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_in_flight_sync() {
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let state = AppState::new(pool.clone(), Settings::default()).expect("Failed to build app state");
+    let service = state.share_service();
+    let share_id = "test-session-shutdown-drain";
+    let _ = sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(&pool)
+        .await;
+    let share = service
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+
+    // Hold the row lock so the sync stays in flight until released
+    let mut lock = pool.begin().await.expect("Failed to begin transaction");
+    sqlx::query("SELECT id FROM shares WHERE id = $1 FOR UPDATE")
+        .bind(share_id)
+        .execute(&mut *lock)
+        .await
+        .expect("Failed to lock share");
+
+    let app = Router::new()
+        .nest("/api", opencode_share::routes::api_routes())
+        .with_state(state);
+    let (listener, addr) = tcp_listener().await;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve(
+        listener,
+        app,
+        async {
+            let _ = shutdown_rx.await;
+        },
+        Duration::from_secs(10),
+    ));
+
+    let body = json!({
+        "secret": share.secret,
+        "data": [{ "_key": "session", "title": "Drained" }]
+    })
+    .to_string();
+    let stream = TcpStream::connect(&addr)
+        .await
+        .expect("Failed to connect to TCP listener");
+    let sync = tokio::spawn(async move {
+        http_post(stream, &format!("/api/share/{}/sync", share_id), &body).await
+    });
+
+    // Wait until the sync is blocked on the row lock
+    let mut blocked = false;
+    for _ in 0..100 {
+        let waiting: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pg_stat_activity WHERE wait_event_type = 'Lock' AND query LIKE '%FROM shares%FOR UPDATE%'",
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to query pg_stat_activity");
+        if waiting > 0 {
+            blocked = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(blocked, "sync request never reached the database");
+
+    shutdown_tx.send(()).expect("Server stopped early");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!server.is_finished(), "server exited with a request in flight");
+
+    lock.commit().await.expect("Failed to release lock");
+
+    let response = sync.await.expect("Sync task panicked");
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    server
+        .await
+        .expect("Server task panicked")
+        .expect("Server returned an error");
+
+    let data = service.get_data(share_id).await.expect("Failed to get data");
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["title"], "Drained");
+}
+
+#[tokio::test]
+async fn test_shutdown_gives_up_after_drain_timeout() {
+    let started = Arc::new(Notify::new());
+    let notify = started.clone();
+    let app = Router::new().route(
+        "/hang",
+        get(move || async move {
+            notify.notify_one();
+            pending::<()>().await;
+        }),
+    );
+
+    let (listener, addr) = tcp_listener().await;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve(
+        listener,
+        app,
+        async {
+            let _ = shutdown_rx.await;
+        },
+        Duration::from_millis(100),
+    ));
+
+    let stream = TcpStream::connect(&addr)
+        .await
+        .expect("Failed to connect to TCP listener");
+    tokio::spawn(http_get(stream, "/hang"));
+    started.notified().await;
+
+    shutdown_tx.send(()).expect("Server stopped early");
+
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("Server did not stop after the drain timeout")
+        .expect("Server task panicked")
+        .expect("Server returned an error");
+}
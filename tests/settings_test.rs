@@ -0,0 +1,174 @@
+// Tests for configuration loading
+
+use opencode_share::settings::{LogFormat, Settings};
+use std::collections::HashMap;
+use std::io::Write;
+
+fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn config_file(content: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(".toml")
+        .tempfile()
+        .expect("Failed to create temp file");
+    file.write_all(content.as_bytes())
+        .expect("Failed to write temp file");
+    file
+}
+
+#[test]
+fn test_defaults() {
+    let settings = Settings::load_from(None, HashMap::new()).expect("Failed to load settings");
+
+    assert_eq!(settings.server.bind_address(), "0.0.0.0:3006");
+    assert_eq!(settings.database.max_connections, 10);
+    assert_eq!(settings.limits.max_sync_items, 1000);
+    assert!(settings.redaction.enabled);
+    assert!(settings.cors.allowed_origins.is_empty());
+    assert_eq!(settings.paths.templates_dir.to_str(), Some("templates"));
+    assert_eq!(settings.logging.format, LogFormat::Text);
+}
+
+#[test]
+fn test_load_from_file() {
+    let file = config_file(
+        r#"
+        [server]
+        host = "127.0.0.1"
+        port = 8080
+        trusted_proxies = ["10.0.0.0/8"]
+
+        [database]
+        url = "postgres://db/share"
+        max_connections = 32
+
+        [limits]
+        max_sync_items = 50
+
+        [redaction]
+        rules = [{ name = "ticket", pattern = "TICKET-[0-9]+" }]
+
+        [cors]
+        allowed_origins = ["https://opencode.ai"]
+
+        [paths]
+        static_dir = "/srv/static"
+        "#,
+    );
+
+    let settings =
+        Settings::load_from(Some(file.path()), HashMap::new()).expect("Failed to load settings");
+
+    assert_eq!(settings.server.bind_address(), "127.0.0.1:8080");
+    assert_eq!(settings.server.trusted_proxies, vec!["10.0.0.0/8"]);
+    assert_eq!(settings.database.url, "postgres://db/share");
+    assert_eq!(settings.database.max_connections, 32);
+    assert_eq!(settings.limits.max_sync_items, 50);
+    // Unset keys in a section keep their defaults
+    assert_eq!(settings.limits.max_item_bytes, 4 * 1024 * 1024);
+    assert_eq!(settings.redaction.rules[0].name, "ticket");
+    assert_eq!(settings.cors.allowed_origins, vec!["https://opencode.ai"]);
+    assert_eq!(settings.paths.static_dir.to_str(), Some("/srv/static"));
+}
+
+#[test]
+fn test_legacy_env_vars_override_file() {
+    let file = config_file(
+        r#"
+        [server]
+        port = 8080
+
+        [database]
+        url = "postgres://db/share"
+        "#,
+    );
+
+    let settings = Settings::load_from(
+        Some(file.path()),
+        env(&[
+            ("PORT", "3000"),
+            ("HOST", "::"),
+            ("DATABASE_URL", "postgres://env/share"),
+            ("MAX_SYNC_ITEMS", "10"),
+            ("TRUSTED_PROXIES", "127.0.0.1, 10.0.0.0/8"),
+            ("REDACTION_ENABLED", "false"),
+            ("LOG_FORMAT", "json"),
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+            ("TEMPLATES_OVERRIDE_DIR", "/etc/opencode-share/templates"),
+            ("TEMPLATES_RELOAD", "false"),
+            ("EMBED_FRAME_ANCESTORS", "'self', https://blog.example.com"),
+            ("ADMIN_TOKEN", "s3cret"),
+        ]),
+    )
+    .expect("Failed to load settings");
+
+    assert_eq!(settings.server.bind_address(), "[::]:3000");
+    assert_eq!(settings.database.url, "postgres://env/share");
+    assert_eq!(settings.limits.max_sync_items, 10);
+    assert_eq!(
+        settings.server.trusted_proxies,
+        vec!["127.0.0.1", "10.0.0.0/8"]
+    );
+    assert!(!settings.redaction.enabled);
+    assert_eq!(settings.logging.format, LogFormat::Json);
+    assert_eq!(
+        settings.telemetry.otlp_endpoint.as_deref(),
+        Some("http://collector:4318")
+    );
+    assert_eq!(
+        settings.paths.templates_override_dir.as_deref(),
+        Some(std::path::Path::new("/etc/opencode-share/templates"))
+    );
+    assert!(!settings.paths.templates_reload);
+    assert_eq!(
+        settings.embed.frame_ancestors,
+        vec!["'self'", "https://blog.example.com"]
+    );
+    assert_eq!(settings.admin.token.as_deref(), Some("s3cret"));
+}
+
+#[test]
+fn test_prefixed_env_vars_take_precedence() {
+    let settings = Settings::load_from(
+        None,
+        env(&[
+            ("PORT", "3000"),
+            ("OPENCODE_SHARE_SERVER__PORT", "4000"),
+            ("OPENCODE_SHARE_DATABASE__MAX_CONNECTIONS", "20"),
+            (
+                "OPENCODE_SHARE_CORS__ALLOWED_ORIGINS",
+                "https://a.example,https://b.example",
+            ),
+        ]),
+    )
+    .expect("Failed to load settings");
+
+    assert_eq!(settings.server.port, 4000);
+    assert_eq!(settings.database.max_connections, 20);
+    assert_eq!(
+        settings.cors.allowed_origins,
+        vec!["https://a.example", "https://b.example"]
+    );
+}
+
+#[test]
+fn test_missing_explicit_config_file() {
+    let result = Settings::load_from(
+        Some(std::path::Path::new("/nonexistent/opencode-share.toml")),
+        HashMap::new(),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_invalid_value() {
+    let result = Settings::load_from(None, env(&[("PORT", "not-a-port")]));
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,138 @@
+// Tests for OpenTelemetry trace export, using a local stand-in for the OTLP collector
+#![cfg(feature = "otel")]
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::from_fn_with_state,
+    routing::post,
+    Router,
+};
+use opencode_share::middleware::access_log_middleware;
+use opencode_share::settings::{Settings, TelemetrySettings};
+use opencode_share::telemetry::tracer_provider;
+use opencode_share::AppState;
+use opentelemetry::trace::TracerProvider as _;
+use sqlx::PgPool;
+use std::env;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+type Exports = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+/// Accepts OTLP/HTTP trace exports and keeps their headers and bodies.
+async fn start_collector() -> (String, Exports) {
+    let exports = Exports::default();
+    let app = Router::new()
+        .route(
+            "/v1/traces",
+            post(
+                |State(exports): State<Exports>, headers: HeaderMap, body: Bytes| async move {
+                    exports.lock().unwrap().push((headers, body));
+                    StatusCode::OK
+                },
+            ),
+        )
+        .with_state(exports.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind collector");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    (format!("http://{}", addr), exports)
+}
+
+fn hex_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+fn test_no_provider_without_endpoint() {
+    let provider = tracer_provider(&TelemetrySettings::default()).expect("Failed to build provider");
+
+    assert!(provider.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_spans_exported_with_incoming_traceparent() {
+    let (endpoint, exports) = start_collector().await;
+    let provider = tracer_provider(&TelemetrySettings {
+        otlp_endpoint: Some(endpoint),
+        ..TelemetrySettings::default()
+    })
+    .expect("Failed to build provider")
+    .expect("No provider for a configured endpoint");
+
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("opencode-share-test")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let database_url = env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost/opencode_share_test".to_string());
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let share_id = "test-session-telemetry";
+    sqlx::query("DELETE FROM shares WHERE id = $1")
+        .bind(share_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clean test database");
+
+    let state = AppState::new(pool, Settings::default()).expect("Failed to build app state");
+    state
+        .share_service()
+        .create(share_id.to_string())
+        .await
+        .expect("Failed to create share");
+
+    let app = Router::new()
+        .nest("/api", opencode_share::routes::api_routes())
+        .layer(from_fn_with_state(state.clone(), access_log_middleware))
+        .with_state(state);
+
+    let request = Request::builder()
+        .uri(format!("/api/share/{}/data", share_id))
+        .header(
+            "traceparent",
+            format!("00-{}-{}-01", TRACE_ID, PARENT_SPAN_ID),
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    provider.force_flush().expect("Failed to flush spans");
+
+    let exports = exports.lock().unwrap();
+    assert!(!exports.is_empty(), "collector received no exports");
+    let (headers, body) = &exports[0];
+    assert_eq!(headers["content-type"], "application/x-protobuf");
+
+    // Spans continue the caller's trace and cover HTTP handling, the service and the query
+    assert!(contains(body, &hex_bytes(TRACE_ID)));
+    assert!(contains(body, &hex_bytes(PARENT_SPAN_ID)));
+    assert!(contains(body, b"GET /api/share/:share_id/data"));
+    assert!(contains(body, b"share.get_data"));
+    assert!(contains(body, b"SELECT shares"));
+}
@@ -0,0 +1,87 @@
+// Tests for share page templates
+
+use minijinja::context;
+use opencode_share::settings::PathSettings;
+use opencode_share::templates::{Templates, SHARE_TEMPLATE};
+use std::fs;
+use std::path::PathBuf;
+
+fn paths(override_dir: Option<PathBuf>, reload: bool) -> PathSettings {
+    PathSettings {
+        templates_override_dir: override_dir,
+        templates_reload: reload,
+        ..PathSettings::default()
+    }
+}
+
+#[test]
+fn test_share_template_escapes_share_id() {
+    let templates = Templates::new(&paths(None, false)).expect("Failed to load templates");
+
+    let html = templates
+        .render(
+            SHARE_TEMPLATE,
+            context! { share_id => "</script><b>x</b>", content => "<div>rendered</div>" },
+        )
+        .expect("Failed to render");
+
+    assert!(html.contains("<title>Opencode Share - &lt;&#x2f;script&gt;&lt;b&gt;x&lt;&#x2f;b&gt;</title>"));
+    assert!(html.contains(
+        r#"window.SHARE_ID = "\u003c/script\u003e\u003cb\u003ex\u003c/b\u003e";"#
+    ));
+    // Pre-rendered conversation markup is inserted as is
+    assert!(html.contains(r#"<div id="app"><div>rendered</div></div>"#));
+}
+
+#[test]
+fn test_override_dir_takes_precedence() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    fs::write(dir.path().join(SHARE_TEMPLATE), "Acme share {{ share_id }}").unwrap();
+
+    let templates = Templates::new(&paths(Some(dir.path().to_path_buf()), false))
+        .expect("Failed to load templates");
+    let html = templates
+        .render(SHARE_TEMPLATE, context! { share_id => "abc" })
+        .expect("Failed to render");
+
+    assert_eq!(html, "Acme share abc");
+}
+
+#[test]
+fn test_override_dir_falls_back_to_templates_dir() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    fs::write(dir.path().join("footer.html"), "unused").unwrap();
+
+    let templates = Templates::new(&paths(Some(dir.path().to_path_buf()), false))
+        .expect("Failed to load templates");
+    let html = templates
+        .render(SHARE_TEMPLATE, context! { share_id => "abc", content => "" })
+        .expect("Failed to render");
+
+    assert!(html.contains("<title>Opencode Share - abc</title>"));
+}
+
+#[test]
+fn test_templates_reload() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join(SHARE_TEMPLATE);
+    fs::write(&path, "v1").unwrap();
+
+    let cached = Templates::new(&paths(Some(dir.path().to_path_buf()), false)).unwrap();
+    let reloading = Templates::new(&paths(Some(dir.path().to_path_buf()), true)).unwrap();
+    assert_eq!(cached.render(SHARE_TEMPLATE, context! {}).unwrap(), "v1");
+    assert_eq!(reloading.render(SHARE_TEMPLATE, context! {}).unwrap(), "v1");
+
+    fs::write(&path, "v2").unwrap();
+
+    assert_eq!(cached.render(SHARE_TEMPLATE, context! {}).unwrap(), "v1");
+    assert_eq!(reloading.render(SHARE_TEMPLATE, context! {}).unwrap(), "v2");
+}
+
+#[test]
+fn test_broken_template_fails_at_startup() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    fs::write(dir.path().join(SHARE_TEMPLATE), "{% if %}").unwrap();
+
+    assert!(Templates::new(&paths(Some(dir.path().to_path_buf()), false)).is_err());
+}